@@ -1,6 +1,3 @@
-use lazy_static::lazy_static;
-use regex::Regex;
-
 pub const ESCAPE_CHARACTER: char = '\\';
 
 pub const ESCAPED_ESCAPE_CHARACTER: &str = "\\\\";
@@ -15,6 +12,7 @@ pub const INTEGER_SMALL_TOKENS: [char; 19] = [
 pub const INTEGER_SMALL_TOKEN_ELEMENT_OFFSET: i64 = 9;
 pub const INTEGER_SMALL_EXCLUSIVE_BOUND_LOWER: i64 = -10;
 pub const INTEGER_SMALL_EXCLUSIVE_BOUND_UPPER: i64 = 10;
+pub const MAX_SAFE_INTEGER: i128 = 9_007_199_254_740_991;
 pub const BASE_62: [char; 62] = [
     '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', 'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I',
     'J', 'K', 'L', 'M', 'N', 'O', 'P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z', 'a', 'b',
@@ -41,11 +39,7 @@ pub const LP_DATE_TOKEN: char = '±';
 pub const REF_LP_DATE_TOKEN: char = 'ü';
 pub const UNREFERENCED_LP_DATE_TOKEN: char = 'ÿ';
 pub const DATE_LOW_PRECISION: f64 = 100_000_f64;
-
-lazy_static! {
-    pub static ref DATE_REGEX: Regex =
-        Regex::new(r"^\d{4}-\d{2}-\d{2}T\d{2}:\d{2}:\d{2}(\.\d+)?Z$").unwrap();
-}
+pub const DATE_NEWTYPE_NAME: &str = "$serde_zipson::private::Date";
 
 pub const FLOAT_TOKEN: char = '£';
 pub const UNREFERENCED_FLOAT_TOKEN: char = '¥';
@@ -53,6 +47,14 @@ pub const REF_FLOAT_TOKEN: char = 'Ý';
 pub const FLOAT_FULL_PRECISION_DELIMITER: char = ',';
 pub const FLOAT_REDUCED_PRECISION_DELIMITER: char = '.';
 pub const FLOAT_COMPRESSION_PRECISION: f64 = 1000_f64;
+// floats outside these bounds are written in exponent form, e.g. `1,5e-10`
+pub const FLOAT_EXPONENT_UPPER_BOUND: f64 = 1e18_f64;
+pub const FLOAT_EXPONENT_LOWER_BOUND: f64 = 1e-6_f64;
+pub const FLOAT_REDUCED_NEWTYPE_NAME: &str = "$serde_zipson::private::ReducedFloat";
+pub const FLOAT_FULL_NEWTYPE_NAME: &str = "$serde_zipson::private::FullFloat";
+pub const DECIMAL_NEWTYPE_NAME: &str = "$serde_zipson::private::Decimal";
+pub const VALUE_NEWTYPE_NAME: &str = "$serde_zipson::private::Value";
+pub const RAW_NUMBER_KEY: &str = "$serde_zipson::private::Number";
 
 pub const ARRAY_START_TOKEN: char = '|';
 pub const ARRAY_END_TOKEN: char = '÷';
@@ -0,0 +1,532 @@
+use crate::error::{Error, Result};
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use serde::ser::{self, Serialize};
+
+// Owned copy of a value in the serde data model, so it can be written later
+// and in a different order than it was produced in
+pub(crate) enum Content {
+    Bool(bool),
+    I64(i64),
+    U64(u64),
+    F32(f32),
+    F64(f64),
+    Char(char),
+    String(String),
+    Bytes(Vec<u8>),
+    None,
+    Some(Box<Content>),
+    Unit,
+    UnitStruct(&'static str),
+    UnitVariant(&'static str, u32, &'static str),
+    NewtypeStruct(&'static str, Box<Content>),
+    NewtypeVariant(&'static str, u32, &'static str, Box<Content>),
+    Seq(Vec<Content>),
+    Tuple(Vec<Content>),
+    TupleStruct(&'static str, Vec<Content>),
+    TupleVariant(&'static str, u32, &'static str, Vec<Content>),
+    Map(Vec<(Content, Content)>),
+    Struct(&'static str, Vec<(&'static str, Option<Content>)>),
+    StructVariant(
+        &'static str,
+        u32,
+        &'static str,
+        Vec<(&'static str, Option<Content>)>,
+    ),
+}
+
+impl Serialize for Content {
+    fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::{
+            SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant, SerializeTuple,
+            SerializeTupleStruct, SerializeTupleVariant,
+        };
+
+        match self {
+            Content::Bool(v) => serializer.serialize_bool(*v),
+            Content::I64(v) => serializer.serialize_i64(*v),
+            Content::U64(v) => serializer.serialize_u64(*v),
+            Content::F32(v) => serializer.serialize_f32(*v),
+            Content::F64(v) => serializer.serialize_f64(*v),
+            Content::Char(v) => serializer.serialize_char(*v),
+            Content::String(v) => serializer.serialize_str(v),
+            Content::Bytes(v) => serializer.serialize_bytes(v),
+            Content::None => serializer.serialize_none(),
+            Content::Some(v) => serializer.serialize_some(v),
+            Content::Unit => serializer.serialize_unit(),
+            Content::UnitStruct(name) => serializer.serialize_unit_struct(name),
+            Content::UnitVariant(name, variant_index, variant) => {
+                serializer.serialize_unit_variant(name, *variant_index, variant)
+            }
+            Content::NewtypeStruct(name, v) => serializer.serialize_newtype_struct(name, v),
+            Content::NewtypeVariant(name, variant_index, variant, v) => {
+                serializer.serialize_newtype_variant(name, *variant_index, variant, v)
+            }
+            Content::Seq(elements) => {
+                let mut seq = serializer.serialize_seq(Some(elements.len()))?;
+                for element in elements {
+                    seq.serialize_element(element)?;
+                }
+                seq.end()
+            }
+            Content::Tuple(elements) => {
+                let mut tuple = serializer.serialize_tuple(elements.len())?;
+                for element in elements {
+                    tuple.serialize_element(element)?;
+                }
+                tuple.end()
+            }
+            Content::TupleStruct(name, fields) => {
+                let mut tuple = serializer.serialize_tuple_struct(name, fields.len())?;
+                for field in fields {
+                    tuple.serialize_field(field)?;
+                }
+                tuple.end()
+            }
+            Content::TupleVariant(name, variant_index, variant, fields) => {
+                let mut tuple = serializer.serialize_tuple_variant(
+                    name,
+                    *variant_index,
+                    variant,
+                    fields.len(),
+                )?;
+                for field in fields {
+                    tuple.serialize_field(field)?;
+                }
+                tuple.end()
+            }
+            Content::Map(entries) => {
+                let mut map = serializer.serialize_map(Some(entries.len()))?;
+                for (key, value) in entries {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            }
+            Content::Struct(name, fields) => {
+                let mut structure = serializer.serialize_struct(name, fields.len())?;
+                for (key, value) in fields {
+                    match value {
+                        Some(value) => structure.serialize_field(key, value)?,
+                        None => structure.skip_field(key)?,
+                    }
+                }
+                structure.end()
+            }
+            Content::StructVariant(name, variant_index, variant, fields) => {
+                let mut structure = serializer.serialize_struct_variant(
+                    name,
+                    *variant_index,
+                    variant,
+                    fields.len(),
+                )?;
+                for (key, value) in fields {
+                    match value {
+                        Some(value) => structure.serialize_field(key, value)?,
+                        None => structure.skip_field(key)?,
+                    }
+                }
+                structure.end()
+            }
+        }
+    }
+}
+
+pub(crate) struct ContentSerializer;
+
+impl ser::Serializer for ContentSerializer {
+    type Ok = Content;
+    type Error = Error;
+
+    type SerializeSeq = SerializeSeq;
+    type SerializeTuple = SerializeSeq;
+    type SerializeTupleStruct = SerializeSeq;
+    type SerializeTupleVariant = SerializeSeq;
+    type SerializeMap = SerializeMap;
+    type SerializeStruct = SerializeStruct;
+    type SerializeStructVariant = SerializeStruct;
+
+    fn serialize_bool(self, v: bool) -> Result<Content> {
+        Ok(Content::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Content> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Content> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Content> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Content> {
+        Ok(Content::I64(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Content> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Content> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Content> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Content> {
+        Ok(Content::U64(v))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Content> {
+        Ok(Content::F32(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Content> {
+        Ok(Content::F64(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Content> {
+        Ok(Content::Char(v))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Content> {
+        Ok(Content::String(v.to_string()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Content> {
+        Ok(Content::Bytes(v.to_vec()))
+    }
+
+    fn serialize_none(self) -> Result<Content> {
+        Ok(Content::None)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Content>
+    where
+        T: ?Sized + Serialize,
+    {
+        Ok(Content::Some(Box::new(value.serialize(self)?)))
+    }
+
+    fn serialize_unit(self) -> Result<Content> {
+        Ok(Content::Unit)
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Content> {
+        Ok(Content::UnitStruct(name))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Content> {
+        Ok(Content::UnitVariant(name, variant_index, variant))
+    }
+
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<Content>
+    where
+        T: ?Sized + Serialize,
+    {
+        Ok(Content::NewtypeStruct(
+            name,
+            Box::new(value.serialize(self)?),
+        ))
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Content>
+    where
+        T: ?Sized + Serialize,
+    {
+        Ok(Content::NewtypeVariant(
+            name,
+            variant_index,
+            variant,
+            Box::new(value.serialize(self)?),
+        ))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Ok(SerializeSeq {
+            kind: SeqKind::Seq,
+            elements: Vec::with_capacity(len.unwrap_or(0)),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple> {
+        Ok(SerializeSeq {
+            kind: SeqKind::Tuple,
+            elements: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        Ok(SerializeSeq {
+            kind: SeqKind::TupleStruct(name),
+            elements: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Ok(SerializeSeq {
+            kind: SeqKind::TupleVariant(name, variant_index, variant),
+            elements: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap> {
+        Ok(SerializeMap {
+            entries: Vec::with_capacity(len.unwrap_or(0)),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, name: &'static str, len: usize) -> Result<Self::SerializeStruct> {
+        Ok(SerializeStruct {
+            kind: StructKind::Struct(name),
+            fields: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Ok(SerializeStruct {
+            kind: StructKind::StructVariant(name, variant_index, variant),
+            fields: Vec::with_capacity(len),
+        })
+    }
+}
+
+enum SeqKind {
+    Seq,
+    Tuple,
+    TupleStruct(&'static str),
+    TupleVariant(&'static str, u32, &'static str),
+}
+
+pub(crate) struct SerializeSeq {
+    kind: SeqKind,
+    elements: Vec<Content>,
+}
+
+impl SerializeSeq {
+    fn push<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.elements.push(value.serialize(ContentSerializer)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Content> {
+        Ok(match self.kind {
+            SeqKind::Seq => Content::Seq(self.elements),
+            SeqKind::Tuple => Content::Tuple(self.elements),
+            SeqKind::TupleStruct(name) => Content::TupleStruct(name, self.elements),
+            SeqKind::TupleVariant(name, variant_index, variant) => {
+                Content::TupleVariant(name, variant_index, variant, self.elements)
+            }
+        })
+    }
+}
+
+impl ser::SerializeSeq for SerializeSeq {
+    type Ok = Content;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Content> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for SerializeSeq {
+    type Ok = Content;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Content> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeSeq {
+    type Ok = Content;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Content> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeSeq {
+    type Ok = Content;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Content> {
+        self.finish()
+    }
+}
+
+pub(crate) struct SerializeMap {
+    entries: Vec<(Content, Content)>,
+    key: Option<Content>,
+}
+
+impl ser::SerializeMap for SerializeMap {
+    type Ok = Content;
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.key = Some(key.serialize(ContentSerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| ser::Error::custom("serialize_value called before serialize_key"))?;
+        self.entries
+            .push((key, value.serialize(ContentSerializer)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Content> {
+        Ok(Content::Map(self.entries))
+    }
+}
+
+enum StructKind {
+    Struct(&'static str),
+    StructVariant(&'static str, u32, &'static str),
+}
+
+pub(crate) struct SerializeStruct {
+    kind: StructKind,
+    fields: Vec<(&'static str, Option<Content>)>,
+}
+
+impl SerializeStruct {
+    fn push<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.fields
+            .push((key, Some(value.serialize(ContentSerializer)?)));
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Content> {
+        Ok(match self.kind {
+            StructKind::Struct(name) => Content::Struct(name, self.fields),
+            StructKind::StructVariant(name, variant_index, variant) => {
+                Content::StructVariant(name, variant_index, variant, self.fields)
+            }
+        })
+    }
+}
+
+impl ser::SerializeStruct for SerializeStruct {
+    type Ok = Content;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.push(key, value)
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<()> {
+        self.fields.push((key, None));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Content> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeStruct {
+    type Ok = Content;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.push(key, value)
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<()> {
+        self.fields.push((key, None));
+        Ok(())
+    }
+
+    fn end(self) -> Result<Content> {
+        self.finish()
+    }
+}
@@ -0,0 +1,294 @@
+//! Serialize date-typed values as zipson date tokens.
+//!
+//! Works for `std::time::SystemTime` with the `std` feature, for `chrono::DateTime<Utc>` with the `chrono` feature
+//! and for `time::OffsetDateTime` with the `time` feature.
+//!
+#![cfg_attr(feature = "std", doc = "```")]
+#![cfg_attr(not(feature = "std"), doc = "```ignore")]
+//! use serde::{Deserialize, Serialize};
+//! use std::time::SystemTime;
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Event {
+//!     #[serde(with = "serde_zipson::date")]
+//!     at: SystemTime,
+//! }
+//! ```
+
+use crate::constants::DATE_NEWTYPE_NAME;
+use alloc::format;
+use alloc::string::String;
+use core::fmt;
+use serde::{de, ser, Deserializer, Serializer};
+#[cfg(feature = "std")]
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A value that can be written as milliseconds since the unix epoch.
+pub trait Timestamp: Sized {
+    fn to_millis(&self) -> Option<i64>;
+    fn from_millis(millis: i64) -> Option<Self>;
+}
+
+#[cfg(feature = "chrono")]
+impl Timestamp for chrono::DateTime<chrono::Utc> {
+    fn to_millis(&self) -> Option<i64> {
+        Some(self.timestamp_millis())
+    }
+
+    fn from_millis(millis: i64) -> Option<Self> {
+        chrono::DateTime::from_timestamp_millis(millis)
+    }
+}
+
+// read back in UTC, the offset isn't part of a date token
+#[cfg(feature = "time")]
+impl Timestamp for time::OffsetDateTime {
+    fn to_millis(&self) -> Option<i64> {
+        i64::try_from(self.unix_timestamp_nanos().div_euclid(1_000_000)).ok()
+    }
+
+    fn from_millis(millis: i64) -> Option<Self> {
+        time::OffsetDateTime::from_unix_timestamp_nanos(i128::from(millis) * 1_000_000).ok()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Timestamp for SystemTime {
+    fn to_millis(&self) -> Option<i64> {
+        match self.duration_since(UNIX_EPOCH) {
+            Ok(duration) => i64::try_from(duration.as_millis()).ok(),
+            // rounded towards negative infinity like chrono, so -1.5ms is -2
+            Err(err) => {
+                let duration = err.duration();
+                let millis =
+                    duration.as_millis() + u128::from(duration.subsec_nanos() % 1_000_000 != 0);
+                i64::try_from(millis).ok().map(|v| -v)
+            }
+        }
+    }
+
+    fn from_millis(millis: i64) -> Option<Self> {
+        let duration = Duration::from_millis(millis.unsigned_abs());
+        if millis < 0 {
+            UNIX_EPOCH.checked_sub(duration)
+        } else {
+            UNIX_EPOCH.checked_add(duration)
+        }
+    }
+}
+
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Timestamp,
+    S: Serializer,
+{
+    let millis = value
+        .to_millis()
+        .ok_or_else(|| ser::Error::custom("date is out of range"))?;
+
+    serializer.serialize_newtype_struct(DATE_NEWTYPE_NAME, &millis)
+}
+
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: Timestamp,
+    D: Deserializer<'de>,
+{
+    let millis = deserializer.deserialize_newtype_struct(DATE_NEWTYPE_NAME, MillisVisitor)?;
+
+    T::from_millis(millis)
+        .ok_or_else(|| de::Error::invalid_value(de::Unexpected::Signed(millis), &"a date in range"))
+}
+
+struct MillisVisitor;
+
+impl<'de> de::Visitor<'de> for MillisVisitor {
+    type Value = i64;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a date token or epoch millis")
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<i64, E>
+    where
+        E: de::Error,
+    {
+        Ok(v)
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<i64, E>
+    where
+        E: de::Error,
+    {
+        i64::try_from(v).map_err(|_| de::Error::invalid_value(de::Unexpected::Unsigned(v), &self))
+    }
+
+    // date tokens buffered by serde, e.g. behind `#[serde(flatten)]` or in an
+    // internally tagged enum, come back as their RFC 3339 text
+    fn visit_str<E>(self, v: &str) -> Result<i64, E>
+    where
+        E: de::Error,
+    {
+        scan_rfc3339(v, true).ok_or_else(|| de::Error::invalid_value(de::Unexpected::Str(v), &self))
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<i64, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
+
+/// Scans a `YYYY-MM-DDTHH:MM:SS[.fff]Z` timestamp, or one ending in a `±HH:MM` offset when
+/// `allow_offsets` is set, and returns its epoch millis.
+/// UTC timestamps are only accepted in the exact form date tokens decode to, with three
+/// fraction digits, and offset timestamps can't be more precise than milliseconds.
+pub(crate) fn scan_rfc3339(v: &str, allow_offsets: bool) -> Option<i64> {
+    let b = v.as_bytes();
+    if b.len() < 20
+        || b[4] != b'-'
+        || b[7] != b'-'
+        || b[10] != b'T'
+        || b[13] != b':'
+        || b[16] != b':'
+    {
+        return None;
+    }
+
+    let year = digits(&b[0..4])?;
+    let month = digits(&b[5..7])?;
+    let day = digits(&b[8..10])?;
+    let hour = digits(&b[11..13])?;
+    let minute = digits(&b[14..16])?;
+    let second = digits(&b[17..19])?;
+
+    if !(1..=12).contains(&month)
+        || day < 1
+        || day > days_in_month(year, month)
+        || hour > 23
+        || minute > 59
+        || second > 59
+    {
+        return None;
+    }
+
+    let mut rest = &b[19..];
+    let mut millis = 0;
+    let mut fraction_len = 0;
+    if let [b'.', tail @ ..] = rest {
+        fraction_len = tail.iter().take_while(|ch| ch.is_ascii_digit()).count();
+        if fraction_len == 0 {
+            return None;
+        }
+        for (i, ch) in tail[..fraction_len].iter().enumerate() {
+            let digit = (ch - b'0') as i64;
+            if i < 3 {
+                millis += digit * 10_i64.pow(2 - i as u32);
+            } else if digit != 0 {
+                return None;
+            }
+        }
+        rest = &tail[fraction_len..];
+    }
+
+    let offset_minutes = match rest {
+        [b'Z'] if fraction_len == 3 => 0,
+        [sign @ (b'+' | b'-'), h1, h2, b':', m1, m2] if allow_offsets => {
+            let hours = digits(&[*h1, *h2])?;
+            let minutes = digits(&[*m1, *m2])?;
+            if hours > 23 || minutes > 59 {
+                return None;
+            }
+            let offset = hours * 60 + minutes;
+            if *sign == b'-' {
+                -offset
+            } else {
+                offset
+            }
+        }
+        _ => return None,
+    };
+
+    let days = days_from_civil(year, month, day);
+    let seconds = days * 86_400 + hour * 3_600 + minute * 60 + second - offset_minutes * 60;
+
+    Some(seconds * 1_000 + millis)
+}
+
+/// Formats epoch millis the way JS `Date#toISOString` does, e.g. `2022-02-24T04:31:00.123Z`.
+pub(crate) fn format_rfc3339(millis: i64) -> Option<String> {
+    let days = millis.div_euclid(86_400_000);
+    let millis_of_day = millis.rem_euclid(86_400_000);
+
+    // JS dates span 100 million days either side of the epoch
+    if days.abs() > 100_000_000 {
+        return None;
+    }
+
+    let (year, month, day) = civil_from_days(days);
+
+    let year = if (0..=9999).contains(&year) {
+        format!("{:04}", year)
+    } else {
+        format!("{:+07}", year)
+    };
+
+    Some(format!(
+        "{}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        millis_of_day / 3_600_000,
+        millis_of_day / 60_000 % 60,
+        millis_of_day / 1_000 % 60,
+        millis_of_day % 1_000
+    ))
+}
+
+fn digits(b: &[u8]) -> Option<i64> {
+    b.iter().try_fold(0, |acc, ch| {
+        ch.is_ascii_digit().then(|| acc * 10 + (ch - b'0') as i64)
+    })
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// days since 1970-01-01 in the proleptic gregorian calendar
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_from_march = (month + 9) % 12;
+    let day_of_year = (153 * month_from_march + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+// inverse of `days_from_civil`
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    };
+    let year = year_of_era + era * 400;
+
+    (if month <= 2 { year + 1 } else { year }, month, day)
+}
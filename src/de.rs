@@ -1,38 +1,96 @@
+#[cfg(feature = "arbitrary_precision")]
+use crate::constants::RAW_NUMBER_KEY;
 use crate::constants::{
     ARRAY_END_TOKEN, ARRAY_REPEAT_MANY_TOKEN, ARRAY_REPEAT_TOKEN, ARRAY_START_TOKEN,
-    BOOLEAN_FALSE_TOKEN, BOOLEAN_TRUE_TOKEN, DATE_LOW_PRECISION, DATE_TOKEN,
-    DELIMITING_TOKENS_THRESHOLD, ESCAPE_CHARACTER, FLOAT_COMPRESSION_PRECISION,
-    FLOAT_FULL_PRECISION_DELIMITER, FLOAT_REDUCED_PRECISION_DELIMITER, FLOAT_TOKEN,
-    INTEGER_SMALL_TOKEN_EXCLUSIVE_BOUND_LOWER, INTEGER_SMALL_TOKEN_EXCLUSIVE_BOUND_UPPER,
-    INTEGER_SMALL_TOKEN_OFFSET, INTEGER_TOKEN, LP_DATE_TOKEN, NULL_TOKEN, OBJECT_END_TOKEN,
-    OBJECT_START_TOKEN, REF_DATE_TOKEN, REF_FLOAT_TOKEN, REF_INTEGER_TOKEN, REF_LP_DATE_TOKEN,
-    REF_STRING_TOKEN, STRING_TOKEN, UNREFERENCED_DATE_TOKEN, UNREFERENCED_FLOAT_TOKEN,
-    UNREFERENCED_INTEGER_TOKEN, UNREFERENCED_LP_DATE_TOKEN, UNREFERENCED_STRING_TOKEN,
+    BOOLEAN_FALSE_TOKEN, BOOLEAN_TRUE_TOKEN, DATE_LOW_PRECISION, DATE_NEWTYPE_NAME, DATE_TOKEN,
+    DECIMAL_NEWTYPE_NAME, DELIMITING_TOKENS_THRESHOLD, ESCAPE_CHARACTER,
+    FLOAT_COMPRESSION_PRECISION, FLOAT_FULL_PRECISION_DELIMITER, FLOAT_REDUCED_PRECISION_DELIMITER,
+    FLOAT_TOKEN, INTEGER_SMALL_TOKEN_EXCLUSIVE_BOUND_LOWER,
+    INTEGER_SMALL_TOKEN_EXCLUSIVE_BOUND_UPPER, INTEGER_SMALL_TOKEN_OFFSET, INTEGER_TOKEN,
+    LP_DATE_TOKEN, NULL_TOKEN, OBJECT_END_TOKEN, OBJECT_START_TOKEN, REF_DATE_TOKEN,
+    REF_FLOAT_TOKEN, REF_INTEGER_TOKEN, REF_LP_DATE_TOKEN, REF_STRING_TOKEN, STRING_TOKEN,
+    UNREFERENCED_DATE_TOKEN, UNREFERENCED_FLOAT_TOKEN, UNREFERENCED_INTEGER_TOKEN,
+    UNREFERENCED_LP_DATE_TOKEN, UNREFERENCED_STRING_TOKEN, VALUE_NEWTYPE_NAME,
 };
 use crate::error::{Error, Result};
-use crate::value::{Number, Value};
-use chrono::{DateTime, SecondsFormat, Utc};
-use indexmap::IndexMap;
+use crate::value::{
+    visit_integral_float, visit_wide_integer, DateDeserializer, Map, MapKeyDeserializer, Number,
+    Value,
+};
+use alloc::collections::BTreeSet;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use core::fmt;
 use serde::de::{self, Deserialize, DeserializeSeed, IntoDeserializer, Visitor};
-use std::fmt;
 
 pub struct OrderedIndex {
     strings: Vec<String>,
-    integers: Vec<i64>,
-    floats: Vec<f64>,
-    dates: Vec<String>,
-    lp_dates: Vec<String>,
+    integers: Vec<i128>,
+    floats: Vec<FloatToken>,
+    dates: Vec<i64>,
+    lp_dates: Vec<i64>,
+}
+
+impl OrderedIndex {
+    fn len(&self) -> [usize; 5] {
+        [
+            self.strings.len(),
+            self.integers.len(),
+            self.floats.len(),
+            self.dates.len(),
+            self.lp_dates.len(),
+        ]
+    }
+
+    fn truncate(&mut self, len: [usize; 5]) {
+        let [strings, integers, floats, dates, lp_dates] = len;
+        self.strings.truncate(strings);
+        self.integers.truncate(integers);
+        self.floats.truncate(floats);
+        self.dates.truncate(dates);
+        self.lp_dates.truncate(lp_dates);
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub enum DuplicateKeys {
+    /// Keep the first value of a repeated key and skip the rest.
+    FirstWins,
+    /// Pass every value to the visitor, so maps keep the last one.
+    #[default]
+    LastWins,
+    /// Fail with `Error::DuplicateKey`.
+    Error,
+}
+
+#[derive(Clone, Default)]
+pub struct DeserializerOptions {
+    /// What to do when an object repeats a key.
+    pub duplicate_keys: DuplicateKeys,
+    /// Reject integer tokens for float targets, and float and date tokens for integer targets.
+    /// By default integral floats are accepted as integers, integers as floats and dates
+    /// as epoch millis. The `serde_zipson::date` module still reads dates.
+    pub strict_numbers: bool,
 }
 
 pub struct Deserializer<'de> {
     input: &'de str,
+    input_len: usize,
     index: OrderedIndex,
+    options: DeserializerOptions,
 }
 
 impl<'de> Deserializer<'de> {
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(input: &'de str) -> Self {
+        Self::from_str_with_options(input, DeserializerOptions::default())
+    }
+
+    pub fn from_str_with_options(input: &'de str, options: DeserializerOptions) -> Self {
         Self {
             input,
+            input_len: input.len(),
             index: OrderedIndex {
                 strings: vec![],
                 integers: vec![],
@@ -40,9 +98,15 @@ impl<'de> Deserializer<'de> {
                 dates: vec![],
                 lp_dates: vec![],
             },
+            options,
         }
     }
 
+    // byte offset into the input
+    fn position(&self) -> usize {
+        self.input_len - self.input.len()
+    }
+
     fn peek_char(&mut self) -> Result<char> {
         self.input.chars().next().ok_or(Error::Eof)
     }
@@ -66,12 +130,19 @@ impl<'de> Deserializer<'de> {
                 visitor.visit_i16(ch as i16 - INTEGER_SMALL_TOKEN_OFFSET)
             }
             INTEGER_TOKEN => {
-                let value = self.parse_integer()?;
+                let value = self.parse_wide_integer()?;
                 self.index.integers.push(value);
-                visitor.visit_i64(value)
+                visit_wide_integer(value, visitor)
             }
-            UNREFERENCED_INTEGER_TOKEN => visitor.visit_i64(self.parse_integer()?),
+            UNREFERENCED_INTEGER_TOKEN => visit_wide_integer(self.parse_wide_integer()?, visitor),
             REF_INTEGER_TOKEN => self.deserialize_ref_integer(visitor),
+            // dates are read as epoch millis
+            ch if is_date_token(ch) && !self.options.strict_numbers => {
+                visitor.visit_i64(self.parse_date_token(ch)?)
+            }
+            ch if is_float_token(ch) && !self.options.strict_numbers => {
+                visit_integral_float(self.parse_float_token(ch)?, visitor)
+            }
             _ => Err(Error::ExpectedInteger),
         }
     }
@@ -81,11 +152,21 @@ impl<'de> Deserializer<'de> {
         V: Visitor<'de>,
     {
         let ref_index = self.parse_integer()? as usize;
+        let value = *self
+            .index
+            .integers
+            .get(ref_index)
+            .ok_or(Error::ExpectedInteger)?;
 
-        visitor.visit_i64(*self.index.integers.get(ref_index).unwrap())
+        visit_wide_integer(value, visitor)
     }
 
     fn parse_integer(&mut self) -> Result<i64> {
+        i64::try_from(self.parse_wide_integer()?).map_err(|_| Error::ExpectedInteger)
+    }
+
+    // wide enough for both i64 and u64 values
+    fn parse_wide_integer(&mut self) -> Result<i128> {
         let mut ch = self.next_char()?;
 
         if ch == '0' {
@@ -96,8 +177,8 @@ impl<'de> Deserializer<'de> {
 
         let mut value = 0;
 
-        fn parse_char(ch: char) -> i64 {
-            let code = ch as i64;
+        fn parse_char(ch: char) -> i128 {
+            let code = ch as i128;
             let mut current = code - 48;
             if code >= 97 {
                 current -= 13
@@ -131,8 +212,10 @@ impl<'de> Deserializer<'de> {
             }
 
             ch = self.next_char()?;
-            value *= 62;
-            value += parse_char(ch);
+            value = value
+                .checked_mul(62)
+                .and_then(|value| value.checked_add(parse_char(ch)))
+                .ok_or(Error::ExpectedInteger)?;
         }
 
         if negative {
@@ -148,69 +231,80 @@ impl<'de> Deserializer<'de> {
     {
         let token = self.next_char()?;
 
+        visitor.visit_f64(self.parse_float_token(token)?)
+    }
+
+    fn parse_float_token(&mut self, token: char) -> Result<f64> {
+        self.parse_float_text_token(token)?.to_f64()
+    }
+
+    fn parse_float_text_token(&mut self, token: char) -> Result<FloatToken> {
         match token {
             FLOAT_TOKEN => {
                 let value = self.parse_float()?;
-                self.index.floats.push(value);
-                visitor.visit_f64(value)
+                self.index.floats.push(value.clone());
+                Ok(value)
+            }
+            UNREFERENCED_FLOAT_TOKEN => self.parse_float(),
+            REF_FLOAT_TOKEN => {
+                let ref_index = self.parse_integer()? as usize;
+                self.index
+                    .floats
+                    .get(ref_index)
+                    .cloned()
+                    .ok_or(Error::ExpectedFloat)
             }
-            UNREFERENCED_FLOAT_TOKEN => visitor.visit_f64(self.parse_float()?),
-            REF_FLOAT_TOKEN => self.deserialize_ref_float(visitor),
             _ => Err(Error::ExpectedFloat),
         }
     }
 
-    fn deserialize_ref_float<V>(&mut self, visitor: V) -> Result<V::Value>
-    where
-        V: Visitor<'de>,
-    {
-        let ref_index = self.parse_integer()? as usize;
-
-        visitor.visit_f64(*self.index.floats.get(ref_index).unwrap())
-    }
-
-    fn parse_float(&mut self) -> Result<f64> {
+    fn parse_float(&mut self) -> Result<FloatToken> {
         let negative = self.peek_char()? == '-';
 
-        let integer = self.parse_integer()?;
+        let integer = self.parse_wide_integer()?;
 
         let delimiter_token = self.next_char()?;
 
-        let fraction: f64 = match delimiter_token {
+        match delimiter_token {
             FLOAT_REDUCED_PRECISION_DELIMITER => {
-                self.parse_integer()? as f64 / FLOAT_COMPRESSION_PRECISION
+                Ok(FloatToken::Reduced(integer, self.parse_integer()?))
             }
             FLOAT_FULL_PRECISION_DELIMITER => {
-                let mut res = if negative { "-0." } else { "0." }.to_string();
+                // parsed as a whole like zipson JS does, the fraction may carry an exponent
+                let mut res = if negative && integer == 0 {
+                    "-0".to_string()
+                } else {
+                    integer.to_string()
+                };
+                res.push('.');
 
                 loop {
                     let ch = self.peek_char();
                     match ch {
-                        Ok(ch) => match ch.to_digit(10) {
-                            Some(_) => {
-                                self.next_char()?;
-                                res.push(ch);
-                            }
-                            None => break,
-                        },
-                        Err(Error::Eof) => {
-                            break;
+                        Ok(ch) if ch.is_ascii_digit() || matches!(ch, 'e' | '+' | '-') => {
+                            self.next_char()?;
+                            res.push(ch);
                         }
+                        Ok(_) | Err(Error::Eof) => break,
                         Err(err) => return Err(err),
                     }
                 }
 
-                match res.parse::<f64>() {
-                    Ok(res) => res,
-                    Err(_) => return Err(Error::ExpectedFloat),
-                }
+                res.parse::<f64>().map_err(|_| Error::ExpectedFloat)?;
+                Ok(FloatToken::Full(res))
             }
-            _ => return Err(Error::ExpectedFloat),
-        };
+            _ => Err(Error::ExpectedFloat),
+        }
+    }
 
-        let res = integer as f64 + fraction;
+    // float tokens are handed to decimal targets as text, so no precision is lost to f64
+    fn deserialize_decimal<V>(&mut self, visitor: V) -> Result<V::Value>
+    where
+        V: Visitor<'de>,
+    {
+        let token = self.next_char()?;
 
-        Ok(res)
+        visitor.visit_string(self.parse_float_text_token(token)?.to_decimal_string())
     }
 
     fn deserialize_string<V>(&mut self, visitor: V) -> Result<V::Value>
@@ -230,6 +324,7 @@ impl<'de> Deserializer<'de> {
                 self.next_char()?;
                 self.deserialize_ref_string(visitor)
             }
+            ch if is_date_token(ch) => self.deserialize_date(visitor),
             _ => Err(Error::ExpectedString),
         }
     }
@@ -258,9 +353,7 @@ impl<'de> Deserializer<'de> {
             }
 
             if escaped > 0 {
-                for _ in 0..escaped / 2 {
-                    chars.push(ESCAPE_CHARACTER);
-                }
+                chars.resize(chars.len() + escaped / 2, ESCAPE_CHARACTER);
                 if escaped % 2 == 1 && ch != token {
                     return Err(Error::ExpectedEscapedToken);
                 }
@@ -278,84 +371,140 @@ impl<'de> Deserializer<'de> {
         Ok(res)
     }
 
-    fn deserialize_date<V>(&mut self, visitor: V) -> Result<V::Value>
+    // tuples and compact structs, whose visitors stop reading at their last
+    // element, or at the end token when trailing fields are left out
+    fn deserialize_fixed_seq<V>(&mut self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let token = self.next_char()?;
+        let mut seq = SeqAccess::new(self);
+        let value = visitor.visit_seq(&mut seq)?;
 
-        match token {
-            DATE_TOKEN => {
-                let value = self.parse_date()?;
-                self.index.dates.push(value.clone());
-                visitor.visit_string(value)
-            }
-            UNREFERENCED_DATE_TOKEN => visitor.visit_string(self.parse_date()?),
-            REF_DATE_TOKEN => self.deserialize_ref_date(visitor),
-            _ => Err(Error::ExpectedDate),
+        if seq.ended || self.next_char()? == ARRAY_END_TOKEN {
+            Ok(value)
+        } else {
+            Err(Error::ExpectedArrayEnd)
         }
     }
 
-    fn deserialize_ref_date<V>(&mut self, visitor: V) -> Result<V::Value>
+    fn deserialize_date<V>(&mut self, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let ref_index = self.parse_integer()? as usize;
-
-        visitor.visit_string(self.index.dates.get(ref_index).unwrap().clone())
-    }
-
-    fn parse_date(&mut self) -> Result<String> {
-        let integer = self.parse_integer()?;
-
-        let seconds = integer / 1000;
-        let millis = (integer % 1000) as u32;
-
-        let dt: DateTime<Utc> = DateTime::from_timestamp(seconds, millis * 1_000_000).unwrap();
-        let value = dt.to_rfc3339_opts(SecondsFormat::Millis, true);
+        let token = self.next_char()?;
+        let millis = self.parse_date_token(token)?;
 
-        Ok(value)
+        de::Deserializer::deserialize_any(DateDeserializer::new(millis), visitor)
     }
 
-    fn deserialize_lp_date<V>(&mut self, visitor: V) -> Result<V::Value>
-    where
-        V: Visitor<'de>,
-    {
-        let token = self.next_char()?;
-
+    fn parse_date_token(&mut self, token: char) -> Result<i64> {
         match token {
+            DATE_TOKEN => {
+                let value = self.parse_date()?;
+                self.index.dates.push(value);
+                Ok(value)
+            }
+            UNREFERENCED_DATE_TOKEN => self.parse_date(),
+            REF_DATE_TOKEN => {
+                let ref_index = self.parse_integer()? as usize;
+                self.index
+                    .dates
+                    .get(ref_index)
+                    .copied()
+                    .ok_or(Error::ExpectedDate)
+            }
             LP_DATE_TOKEN => {
                 let value = self.parse_lp_date()?;
-                self.index.lp_dates.push(value.clone());
-                visitor.visit_string(value)
+                self.index.lp_dates.push(value);
+                Ok(value)
             }
-            UNREFERENCED_LP_DATE_TOKEN => visitor.visit_string(self.parse_lp_date()?),
-            REF_LP_DATE_TOKEN => self.deserialize_ref_lp_date(visitor),
-            _ => Err(Error::ExpectedLpDate),
+            UNREFERENCED_LP_DATE_TOKEN => self.parse_lp_date(),
+            REF_LP_DATE_TOKEN => {
+                let ref_index = self.parse_integer()? as usize;
+                self.index
+                    .lp_dates
+                    .get(ref_index)
+                    .copied()
+                    .ok_or(Error::ExpectedLpDate)
+            }
+            _ => Err(Error::ExpectedDate),
         }
     }
 
-    fn deserialize_ref_lp_date<V>(&mut self, visitor: V) -> Result<V::Value>
-    where
-        V: Visitor<'de>,
-    {
-        let ref_index = self.parse_integer()? as usize;
+    fn parse_date(&mut self) -> Result<i64> {
+        self.parse_integer()
+    }
 
-        visitor.visit_string(self.index.lp_dates.get(ref_index).unwrap().clone())
+    fn parse_lp_date(&mut self) -> Result<i64> {
+        self.parse_integer()?
+            .checked_mul(DATE_LOW_PRECISION as i64)
+            .ok_or(Error::ExpectedLpDate)
     }
+}
+
+fn is_date_token(ch: char) -> bool {
+    matches!(
+        ch,
+        DATE_TOKEN
+            | UNREFERENCED_DATE_TOKEN
+            | REF_DATE_TOKEN
+            | LP_DATE_TOKEN
+            | UNREFERENCED_LP_DATE_TOKEN
+            | REF_LP_DATE_TOKEN
+    )
+}
 
-    fn parse_lp_date(&mut self) -> Result<String> {
-        let integer = self.parse_integer()? * DATE_LOW_PRECISION as i64;
+// a float token as written, readable both as f64 and as exact decimal text
+#[derive(Clone)]
+enum FloatToken {
+    Reduced(i128, i64),
+    Full(String),
+}
 
-        let dt: DateTime<Utc> = DateTime::from_timestamp_millis(integer).unwrap();
-        let value = dt.to_rfc3339_opts(SecondsFormat::Millis, true);
+impl FloatToken {
+    fn to_f64(&self) -> Result<f64> {
+        match self {
+            FloatToken::Reduced(integer, fraction) => {
+                Ok(*integer as f64 + *fraction as f64 / FLOAT_COMPRESSION_PRECISION)
+            }
+            FloatToken::Full(text) => text.parse::<f64>().map_err(|_| Error::ExpectedFloat),
+        }
+    }
 
-        Ok(value)
+    fn to_decimal_string(&self) -> String {
+        match self {
+            FloatToken::Reduced(integer, fraction) => {
+                // both parts carry the sign, so `0.-84` is -0.084
+                let precision = FLOAT_COMPRESSION_PRECISION as i128;
+                let thousandths = integer * precision + i128::from(*fraction);
+                let sign = if thousandths < 0 { "-" } else { "" };
+                let thousandths = thousandths.unsigned_abs();
+                format!(
+                    "{}{}.{:03}",
+                    sign,
+                    thousandths / precision as u128,
+                    thousandths % precision as u128
+                )
+            }
+            FloatToken::Full(text) => text.clone(),
+        }
     }
 }
 
+fn is_float_token(ch: char) -> bool {
+    matches!(ch, FLOAT_TOKEN | UNREFERENCED_FLOAT_TOKEN | REF_FLOAT_TOKEN)
+}
+
+fn is_integer_token(ch: char) -> bool {
+    matches!(
+        ch,
+        INTEGER_TOKEN | UNREFERENCED_INTEGER_TOKEN | REF_INTEGER_TOKEN
+    ) || ((ch as u8) > INTEGER_SMALL_TOKEN_EXCLUSIVE_BOUND_LOWER
+        && (ch as u8) < INTEGER_SMALL_TOKEN_EXCLUSIVE_BOUND_UPPER)
+}
+
 impl<'de> Deserialize<'de> for Value {
-    fn deserialize<D>(deserializer: D) -> std::result::Result<Value, D::Error>
+    fn deserialize<D>(deserializer: D) -> core::result::Result<Value, D::Error>
     where
         D: de::Deserializer<'de>,
     {
@@ -368,31 +517,69 @@ impl<'de> Deserialize<'de> for Value {
                 formatter.write_str("a string key")
             }
 
-            fn visit_unit<E>(self) -> std::result::Result<Self::Value, E> {
+            fn visit_unit<E>(self) -> core::result::Result<Self::Value, E> {
                 Ok(Value::Null)
             }
 
-            fn visit_bool<E>(self, bool: bool) -> std::result::Result<Self::Value, E> {
+            fn visit_bool<E>(self, bool: bool) -> core::result::Result<Self::Value, E> {
                 Ok(Value::Bool(bool))
             }
 
-            fn visit_string<E>(self, str: String) -> std::result::Result<Value, E> {
+            fn visit_str<E>(self, str: &str) -> core::result::Result<Value, E> {
+                Ok(Value::String(str.to_string()))
+            }
+
+            fn visit_string<E>(self, str: String) -> core::result::Result<Value, E> {
                 Ok(Value::String(str))
             }
 
-            fn visit_i64<E>(self, number: i64) -> std::result::Result<Value, E> {
+            fn visit_i64<E>(self, number: i64) -> core::result::Result<Value, E> {
                 Ok(Value::Number(Number::Int(number)))
             }
 
-            fn visit_u64<E>(self, number: u64) -> std::result::Result<Value, E> {
-                Ok(Value::Number(Number::Int(number as i64)))
+            fn visit_u64<E>(self, number: u64) -> core::result::Result<Value, E> {
+                Ok(Value::Number(match i64::try_from(number) {
+                    Ok(number) => Number::Int(number),
+                    Err(_) => Number::UInt(number),
+                }))
             }
 
-            fn visit_f64<E>(self, number: f64) -> std::result::Result<Value, E> {
+            fn visit_f64<E>(self, number: f64) -> core::result::Result<Value, E> {
                 Ok(Value::Number(Number::Float(number)))
             }
 
-            fn visit_seq<A>(self, mut seq: A) -> std::result::Result<Value, A::Error>
+            // integers beyond `u64` only come from decimals
+            fn visit_i128<E>(self, number: i128) -> core::result::Result<Value, E>
+            where
+                E: de::Error,
+            {
+                if let Ok(number) = i64::try_from(number) {
+                    return self.visit_i64(number);
+                }
+                if let Ok(number) = u64::try_from(number) {
+                    return self.visit_u64(number);
+                }
+
+                #[cfg(feature = "arbitrary_precision")]
+                return Ok(Value::Number(Number::Raw(number.to_string())));
+                #[cfg(not(feature = "arbitrary_precision"))]
+                return Ok(Value::Number(Number::Float(number as f64)));
+            }
+
+            fn visit_u128<E>(self, number: u128) -> core::result::Result<Value, E>
+            where
+                E: de::Error,
+            {
+                match i128::try_from(number) {
+                    Ok(number) => self.visit_i128(number),
+                    #[cfg(feature = "arbitrary_precision")]
+                    Err(_) => Ok(Value::Number(Number::Raw(number.to_string()))),
+                    #[cfg(not(feature = "arbitrary_precision"))]
+                    Err(_) => Ok(Value::Number(Number::Float(number as f64))),
+                }
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> core::result::Result<Value, A::Error>
             where
                 A: de::SeqAccess<'de>,
             {
@@ -405,25 +592,45 @@ impl<'de> Deserialize<'de> for Value {
                 Ok(Value::Array(vec))
             }
 
-            fn visit_map<A>(self, mut seq: A) -> std::result::Result<Value, A::Error>
+            fn visit_newtype_struct<D>(
+                self,
+                deserializer: D,
+            ) -> core::result::Result<Value, D::Error>
+            where
+                D: de::Deserializer<'de>,
+            {
+                deserializer.deserialize_any(self)
+            }
+
+            fn visit_map<A>(self, mut seq: A) -> core::result::Result<Value, A::Error>
             where
                 A: de::MapAccess<'de>,
             {
-                let mut map = IndexMap::new();
+                let mut map = Map::new();
+
+                while let Some(key) = seq.next_key::<String>()? {
+                    #[cfg(feature = "arbitrary_precision")]
+                    if map.is_empty() && key == RAW_NUMBER_KEY {
+                        return Ok(Value::Number(Number::Raw(seq.next_value()?)));
+                    }
 
-                while let Some((key, value)) = seq.next_entry()? {
-                    map.insert(key, value);
+                    map.insert(key, seq.next_value()?);
                 }
 
                 Ok(Value::Object(map))
             }
         }
 
+        // see `deserialize_newtype_struct`
+        if cfg!(feature = "arbitrary_precision") {
+            return deserializer.deserialize_newtype_struct(VALUE_NEWTYPE_NAME, ValueVisitor);
+        }
+
         deserializer.deserialize_any(ValueVisitor)
     }
 }
 
-impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
+impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
     type Error = Error;
 
     fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
@@ -436,24 +643,14 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
             NULL_TOKEN => self.deserialize_unit(visitor),
             BOOLEAN_TRUE_TOKEN => self.deserialize_bool(visitor),
             BOOLEAN_FALSE_TOKEN => self.deserialize_bool(visitor),
-            ch if (ch as u8) > INTEGER_SMALL_TOKEN_EXCLUSIVE_BOUND_LOWER
-                && (ch as u8) < INTEGER_SMALL_TOKEN_EXCLUSIVE_BOUND_UPPER =>
-            {
-                self.deserialize_integer(visitor)
-            }
-            INTEGER_TOKEN | UNREFERENCED_INTEGER_TOKEN | REF_INTEGER_TOKEN => {
-                self.deserialize_integer(visitor)
-            }
+            ch if is_integer_token(ch) => self.deserialize_integer(visitor),
             FLOAT_TOKEN | UNREFERENCED_FLOAT_TOKEN | REF_FLOAT_TOKEN => {
                 self.deserialize_float(visitor)
             }
             STRING_TOKEN | UNREFERENCED_STRING_TOKEN | REF_STRING_TOKEN => {
                 self.deserialize_str(visitor)
             }
-            DATE_TOKEN | UNREFERENCED_DATE_TOKEN | REF_DATE_TOKEN => self.deserialize_date(visitor),
-            LP_DATE_TOKEN | UNREFERENCED_LP_DATE_TOKEN | REF_LP_DATE_TOKEN => {
-                self.deserialize_lp_date(visitor)
-            }
+            ch if is_date_token(ch) => self.deserialize_date(visitor),
             ARRAY_START_TOKEN => self.deserialize_seq(visitor),
             OBJECT_START_TOKEN => self.deserialize_map(visitor),
             _ => Err(Error::Syntax),
@@ -539,6 +736,11 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
+        // zipson JS writes integral floats as integers
+        if !self.options.strict_numbers && is_integer_token(self.peek_char()?) {
+            return self.deserialize_integer(visitor);
+        }
+
         self.deserialize_float(visitor)
     }
 
@@ -606,10 +808,35 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
         self.deserialize_unit(visitor)
     }
 
-    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    fn deserialize_newtype_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
+        if name == DECIMAL_NEWTYPE_NAME && is_float_token(self.peek_char()?) {
+            return self.deserialize_decimal(visitor);
+        }
+        if name == DATE_NEWTYPE_NAME && is_date_token(self.peek_char()?) {
+            let token = self.next_char()?;
+            return visitor.visit_i64(self.parse_date_token(token)?);
+        }
+        // with `arbitrary_precision`, `Value` gets full precision floats as their text,
+        // in a map holding just the `RAW_NUMBER_KEY`
+        #[cfg(feature = "arbitrary_precision")]
+        if name == VALUE_NEWTYPE_NAME {
+            let token = self.peek_char()?;
+            if !is_float_token(token) {
+                return self.deserialize_any(visitor);
+            }
+
+            self.next_char()?;
+            return match self.parse_float_text_token(token)? {
+                FloatToken::Full(text) => visitor.visit_map(de::value::MapDeserializer::new(
+                    core::iter::once((RAW_NUMBER_KEY, text)),
+                )),
+                token => visitor.visit_f64(token.to_f64()?),
+            };
+        }
+
         visitor.visit_newtype_struct(self)
     }
 
@@ -625,9 +852,8 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        let res = self.deserialize_seq(visitor);
         self.next_char()?;
-        res
+        self.deserialize_fixed_seq(visitor)
     }
 
     fn deserialize_tuple_struct<V>(
@@ -652,15 +878,26 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
 
     fn deserialize_struct<V>(
         self,
-        _name: &'static str,
-        _fields: &'static [&'static str],
+        name: &'static str,
+        fields: &'static [&'static str],
         visitor: V,
     ) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        let _ = self.next_char()?;
-        visitor.visit_map(MapAccess::new(self))
+        match self.next_char()? {
+            ch if is_date_token(ch) => {
+                let millis = self.parse_date_token(ch)?;
+                de::Deserializer::deserialize_struct(
+                    DateDeserializer::new(millis),
+                    name,
+                    fields,
+                    visitor,
+                )
+            }
+            ARRAY_START_TOKEN => self.deserialize_fixed_seq(visitor),
+            _ => visitor.visit_map(MapAccess::new(self)),
+        }
     }
 
     fn deserialize_enum<V>(
@@ -672,18 +909,29 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        if self.peek_char()? == STRING_TOKEN {
-            visitor.visit_enum(self.parse_string()?.into_deserializer())
-        } else if self.next_char()? == OBJECT_START_TOKEN {
-            let value = visitor.visit_enum(&mut *self)?;
-
-            if self.next_char()? == OBJECT_END_TOKEN {
-                Ok(value)
-            } else {
-                Err(Error::ExpectedMapEnd)
+        match self.peek_char()? {
+            STRING_TOKEN | UNREFERENCED_STRING_TOKEN | REF_STRING_TOKEN => {
+                visitor.visit_enum(String::deserialize(&mut *self)?.into_deserializer())
             }
-        } else {
-            Err(Error::ExpectedEnum)
+            ch if is_integer_token(ch) => {
+                visitor.visit_enum(u32::deserialize(&mut *self)?.into_deserializer())
+            }
+            OBJECT_START_TOKEN | ARRAY_START_TOKEN => {
+                let (end_token, end_error) = if self.next_char()? == OBJECT_START_TOKEN {
+                    (OBJECT_END_TOKEN, Error::ExpectedMapEnd)
+                } else {
+                    (ARRAY_END_TOKEN, Error::ExpectedArrayEnd)
+                };
+
+                let value = visitor.visit_enum(&mut *self)?;
+
+                if self.next_char()? == end_token {
+                    Ok(value)
+                } else {
+                    Err(end_error)
+                }
+            }
+            _ => Err(Error::ExpectedEnum),
         }
     }
 
@@ -691,7 +939,11 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
     where
         V: Visitor<'de>,
     {
-        self.deserialize_string(visitor)
+        if is_integer_token(self.peek_char()?) {
+            visitor.visit_u64(u64::deserialize(self)?)
+        } else {
+            self.deserialize_string(visitor)
+        }
     }
 
     fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value>
@@ -704,39 +956,41 @@ impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
 
 struct SeqAccess<'a, 'de: 'a> {
     de: &'a mut Deserializer<'de>,
-    last_value: Option<Value>,
+    // input of the last element, parsed again for repeat tokens
+    last_input: Option<&'de str>,
     repeat: i64,
+    // whether the end token was read
+    ended: bool,
 }
 
 impl<'a, 'de: 'a> SeqAccess<'a, 'de> {
     fn new(de: &'a mut Deserializer<'de>) -> Self {
         Self {
             de,
-            last_value: None,
+            last_input: None,
             repeat: 0,
+            ended: false,
         }
     }
 
-    fn get_last(&mut self) -> Result<Value> {
-        let next_char = self.de.peek_char()?;
+    fn repeat_last<T>(&mut self, seed: T) -> Result<T::Value>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        let last_input = self.last_input.ok_or(Error::UnexpectedRepeatToken)?;
 
-        let res = if self.repeat > 1
-            || next_char == ARRAY_REPEAT_TOKEN
-            || next_char == ARRAY_REPEAT_MANY_TOKEN
-        {
-            self.last_value
-                .as_ref()
-                .ok_or(Error::UnexpectedRepeatToken)?
-                .clone()
-        } else {
-            self.last_value.take().ok_or(Error::UnexpectedRepeatToken)?
-        };
+        // the element already added its strings, numbers and dates to the index
+        let index_len = self.de.index.len();
+        let input = core::mem::replace(&mut self.de.input, last_input);
+        let res = seed.deserialize(&mut *self.de);
+        self.de.input = input;
+        self.de.index.truncate(index_len);
 
         if self.repeat > 0 {
             self.repeat -= 1;
         }
 
-        Ok(res)
+        res
     }
 }
 
@@ -746,26 +1000,24 @@ impl<'de, 'a> de::SeqAccess<'de> for SeqAccess<'a, 'de> {
     fn next_element_seed<T>(
         &mut self,
         seed: T,
-    ) -> std::result::Result<Option<T::Value>, Self::Error>
+    ) -> core::result::Result<Option<T::Value>, Self::Error>
     where
         T: DeserializeSeed<'de>,
     {
         if self.repeat > 0 {
-            let last_value = self.get_last()?;
-            return Ok(Some(seed.deserialize(last_value)?));
+            return self.repeat_last(seed).map(Some);
         }
 
         match self.de.peek_char()? {
             ARRAY_END_TOKEN => {
                 self.de.next_char()?;
+                self.ended = true;
                 Ok(None)
             }
             ARRAY_REPEAT_TOKEN => {
                 self.de.next_char()?;
 
-                let last_value = self.get_last()?;
-
-                Ok(Some(seed.deserialize(last_value)?))
+                self.repeat_last(seed).map(Some)
             }
             ARRAY_REPEAT_MANY_TOKEN => {
                 self.de.next_char()?;
@@ -778,20 +1030,13 @@ impl<'de, 'a> de::SeqAccess<'de> for SeqAccess<'a, 'de> {
                     return self.next_element_seed(seed);
                 }
 
-                let last_value = self.get_last()?;
-
-                Ok(Some(seed.deserialize(last_value)?))
+                self.repeat_last(seed).map(Some)
             }
             _ => {
-                let v = Value::deserialize(&mut *self.de)?;
-                let next_char = self.de.peek_char()?;
-                self.last_value =
-                    if next_char == ARRAY_REPEAT_TOKEN || next_char == ARRAY_REPEAT_MANY_TOKEN {
-                        Some(v.clone())
-                    } else {
-                        None
-                    };
-                Ok(Some(seed.deserialize(v)?))
+                let input = self.de.input;
+                let res = seed.deserialize(&mut *self.de)?;
+                self.last_input = Some(&input[..input.len() - self.de.input.len()]);
+                Ok(Some(res))
             }
         }
     }
@@ -799,35 +1044,61 @@ impl<'de, 'a> de::SeqAccess<'de> for SeqAccess<'a, 'de> {
 
 struct MapAccess<'a, 'de: 'a> {
     de: &'a mut Deserializer<'de>,
+    keys: BTreeSet<String>,
 }
 
 impl<'a, 'de: 'a> MapAccess<'a, 'de> {
     fn new(de: &'a mut Deserializer<'de>) -> Self {
-        Self { de }
+        Self {
+            de,
+            keys: BTreeSet::new(),
+        }
     }
 }
 
 impl<'de, 'a> de::MapAccess<'de> for MapAccess<'a, 'de> {
     type Error = Error;
 
-    fn next_key_seed<K>(&mut self, seed: K) -> std::result::Result<Option<K::Value>, Self::Error>
+    fn next_key_seed<K>(&mut self, seed: K) -> core::result::Result<Option<K::Value>, Self::Error>
     where
         K: DeserializeSeed<'de>,
     {
-        match self.de.peek_char()? {
-            OBJECT_END_TOKEN => {
-                self.de.next_char()?;
-                Ok(None)
+        loop {
+            match self.de.peek_char()? {
+                OBJECT_END_TOKEN => {
+                    self.de.next_char()?;
+                    return Ok(None);
+                }
+                STRING_TOKEN | UNREFERENCED_STRING_TOKEN | REF_STRING_TOKEN => {
+                    let position = self.de.position();
+                    let key = String::deserialize(&mut *self.de)?;
+
+                    match self.de.options.duplicate_keys {
+                        DuplicateKeys::LastWins => {}
+                        _ if !self.keys.contains(&key) => {
+                            self.keys.insert(key.clone());
+                        }
+                        DuplicateKeys::FirstWins => {
+                            de::IgnoredAny::deserialize(&mut *self.de)?;
+                            continue;
+                        }
+                        DuplicateKeys::Error => {
+                            return Err(Error::DuplicateKey { key, position });
+                        }
+                    }
+
+                    return Ok(Some(seed.deserialize(MapKeyDeserializer::new(key))?));
+                }
+                _ => return Ok(Some(seed.deserialize(&mut *self.de)?)),
             }
-            _ => Ok(Some(seed.deserialize(&mut *self.de)?)),
         }
     }
 
-    fn next_value_seed<V>(&mut self, seed: V) -> std::result::Result<V::Value, Self::Error>
+    fn next_value_seed<V>(&mut self, seed: V) -> core::result::Result<V::Value, Self::Error>
     where
         V: DeserializeSeed<'de>,
     {
-        Ok(seed.deserialize(&mut *self.de)?)
+        seed.deserialize(&mut *self.de)
     }
 }
 
@@ -835,7 +1106,14 @@ pub fn from_str<'a, T>(s: &'a str) -> Result<T>
 where
     T: Deserialize<'a>,
 {
-    let mut deserializer = Deserializer::from_str(s);
+    from_str_with_options(s, DeserializerOptions::default())
+}
+
+pub fn from_str_with_options<'a, T>(s: &'a str, options: DeserializerOptions) -> Result<T>
+where
+    T: Deserialize<'a>,
+{
+    let mut deserializer = Deserializer::from_str_with_options(s, options);
     let t = T::deserialize(&mut deserializer)?;
     if deserializer.input.is_empty() {
         Ok(t)
@@ -844,7 +1122,7 @@ where
     }
 }
 
-impl<'de, 'a> de::EnumAccess<'de> for &'a mut Deserializer<'de> {
+impl<'de> de::EnumAccess<'de> for &mut Deserializer<'de> {
     type Error = Error;
     type Variant = Self;
 
@@ -858,7 +1136,7 @@ impl<'de, 'a> de::EnumAccess<'de> for &'a mut Deserializer<'de> {
     }
 }
 
-impl<'de, 'a> de::VariantAccess<'de> for &'a mut Deserializer<'de> {
+impl<'de> de::VariantAccess<'de> for &mut Deserializer<'de> {
     type Error = Error;
 
     fn unit_variant(self) -> Result<()> {
@@ -879,10 +1157,10 @@ impl<'de, 'a> de::VariantAccess<'de> for &'a mut Deserializer<'de> {
         de::Deserializer::deserialize_tuple(self, len, visitor)
     }
 
-    fn struct_variant<V>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value>
+    fn struct_variant<V>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value>
     where
         V: Visitor<'de>,
     {
-        de::Deserializer::deserialize_map(self, visitor)
+        de::Deserializer::deserialize_struct(self, "", fields, visitor)
     }
 }
@@ -0,0 +1,85 @@
+//! Serialize `rust_decimal::Decimal` values as exact full precision float tokens,
+//! or integer tokens when the scale is 0.
+//!
+//! ```
+//! use rust_decimal::Decimal;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Payment {
+//!     #[serde(with = "serde_zipson::decimal")]
+//!     amount: Decimal,
+//! }
+//! ```
+
+use crate::constants::DECIMAL_NEWTYPE_NAME;
+use alloc::string::ToString;
+use core::fmt;
+use rust_decimal::Decimal;
+use serde::{de, Deserializer, Serializer};
+
+pub fn serialize<S>(value: &Decimal, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_newtype_struct(DECIMAL_NEWTYPE_NAME, &value.to_string())
+}
+
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Decimal, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_newtype_struct(DECIMAL_NEWTYPE_NAME, DecimalVisitor)
+}
+
+struct DecimalVisitor;
+
+impl<'de> de::Visitor<'de> for DecimalVisitor {
+    type Value = Decimal;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a decimal number")
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Decimal, E>
+    where
+        E: de::Error,
+    {
+        Ok(Decimal::from(v))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Decimal, E>
+    where
+        E: de::Error,
+    {
+        Ok(Decimal::from(v))
+    }
+
+    fn visit_i128<E>(self, v: i128) -> Result<Decimal, E>
+    where
+        E: de::Error,
+    {
+        Decimal::try_from_i128_with_scale(v, 0)
+            .map_err(|_| de::Error::invalid_value(de::Unexpected::Other("i128"), &self))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Decimal, E>
+    where
+        E: de::Error,
+    {
+        let res = if v.contains(['e', 'E']) {
+            Decimal::from_scientific(v)
+        } else {
+            Decimal::from_str_exact(v)
+        };
+
+        res.map_err(|_| de::Error::invalid_value(de::Unexpected::Str(v), &self))
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Decimal, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
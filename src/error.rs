@@ -1,9 +1,9 @@
-use std;
-use std::fmt::{self, Display};
+use alloc::string::{String, ToString};
+use core::fmt::{self, Display};
 
 use serde::{de, ser};
 
-pub type Result<T> = std::result::Result<T, Error>;
+pub type Result<T> = core::result::Result<T, Error>;
 
 // This is a bare-bones implementation. A real library would provide additional
 // information in its error type, for example the line and column at which the
@@ -40,6 +40,10 @@ pub enum Error {
     ExpectedMapComma,
     ExpectedMapEnd,
     ExpectedEnum,
+    KeyMustBeAString,
+    FloatMustBeFinite,
+    UnsafeInteger,
+    DuplicateKey { key: String, position: usize },
     TrailingCharacters,
     UnexpectedRepeatToken,
 }
@@ -61,9 +65,21 @@ impl Display for Error {
         match self {
             Error::Message(msg) => formatter.write_str(msg),
             Error::Eof => formatter.write_str("unexpected end of input"),
+            Error::KeyMustBeAString => formatter.write_str("key must be a string"),
+            Error::FloatMustBeFinite => formatter.write_str("float must be finite"),
+            Error::UnsafeInteger => {
+                formatter.write_str("integer is outside of the JavaScript safe integer range")
+            }
+            Error::DuplicateKey { key, position } => {
+                write!(
+                    formatter,
+                    "duplicate key `{}` at position {}",
+                    key, position
+                )
+            }
             _ => formatter.write_str("unhandled error"),
         }
     }
 }
 
-impl std::error::Error for Error {}
+impl serde::de::StdError for Error {}
@@ -0,0 +1,58 @@
+//! Force a float encoding for one field, whatever the serializer options say.
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Reading {
+//!     #[serde(with = "serde_zipson::float::reduced")]
+//!     temperature: f64,
+//!     #[serde(with = "serde_zipson::float::full")]
+//!     price: Option<f64>,
+//! }
+//! ```
+
+use crate::constants::{FLOAT_FULL_NEWTYPE_NAME, FLOAT_REDUCED_NEWTYPE_NAME};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Floats rounded to 3 decimals.
+pub mod reduced {
+    use super::*;
+
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: ?Sized + Serialize,
+        S: Serializer,
+    {
+        serializer.serialize_newtype_struct(FLOAT_REDUCED_NEWTYPE_NAME, value)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        T::deserialize(deserializer)
+    }
+}
+
+/// Floats with all their decimals.
+pub mod full {
+    use super::*;
+
+    pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        T: ?Sized + Serialize,
+        S: Serializer,
+    {
+        serializer.serialize_newtype_struct(FLOAT_FULL_NEWTYPE_NAME, value)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: Deserialize<'de>,
+        D: Deserializer<'de>,
+    {
+        T::deserialize(deserializer)
+    }
+}
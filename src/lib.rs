@@ -1,6 +1,15 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
 pub mod constants;
+mod content;
+pub mod date;
 pub mod de;
+#[cfg(feature = "decimal")]
+pub mod decimal;
 pub mod error;
+pub mod float;
 pub mod ser;
 pub mod value;
 
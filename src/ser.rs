@@ -1,57 +1,162 @@
 use crate::constants::{
     ARRAY_END_TOKEN, ARRAY_REPEAT_COUNT_THRESHOLD, ARRAY_REPEAT_MANY_TOKEN, ARRAY_REPEAT_TOKEN,
     ARRAY_START_TOKEN, BASE_62, BOOLEAN_FALSE_TOKEN, BOOLEAN_TRUE_TOKEN, DATE_LOW_PRECISION,
-    DATE_REGEX, DATE_TOKEN, ESCAPED_ESCAPE_CHARACTER, ESCAPED_STRING_TOKEN,
-    ESCAPED_UNREFERENCED_STRING_TOKEN, ESCAPE_CHARACTER, FLOAT_COMPRESSION_PRECISION,
-    FLOAT_FULL_PRECISION_DELIMITER, FLOAT_REDUCED_PRECISION_DELIMITER, FLOAT_TOKEN,
-    INTEGER_SMALL_EXCLUSIVE_BOUND_LOWER, INTEGER_SMALL_EXCLUSIVE_BOUND_UPPER, INTEGER_SMALL_TOKENS,
-    INTEGER_SMALL_TOKEN_ELEMENT_OFFSET, INTEGER_TOKEN, LP_DATE_TOKEN, NULL_TOKEN, OBJECT_END_TOKEN,
+    DATE_NEWTYPE_NAME, DATE_TOKEN, DECIMAL_NEWTYPE_NAME, ESCAPED_ESCAPE_CHARACTER,
+    ESCAPED_STRING_TOKEN, ESCAPED_UNREFERENCED_STRING_TOKEN, ESCAPE_CHARACTER,
+    FLOAT_COMPRESSION_PRECISION, FLOAT_EXPONENT_LOWER_BOUND, FLOAT_EXPONENT_UPPER_BOUND,
+    FLOAT_FULL_NEWTYPE_NAME, FLOAT_FULL_PRECISION_DELIMITER, FLOAT_REDUCED_NEWTYPE_NAME,
+    FLOAT_REDUCED_PRECISION_DELIMITER, FLOAT_TOKEN, INTEGER_SMALL_EXCLUSIVE_BOUND_LOWER,
+    INTEGER_SMALL_EXCLUSIVE_BOUND_UPPER, INTEGER_SMALL_TOKENS, INTEGER_SMALL_TOKEN_ELEMENT_OFFSET,
+    INTEGER_TOKEN, LP_DATE_TOKEN, MAX_SAFE_INTEGER, NULL_TOKEN, OBJECT_END_TOKEN,
     OBJECT_START_TOKEN, REF_DATE_TOKEN, REF_FLOAT_TOKEN, REF_INTEGER_TOKEN, REF_LP_DATE_TOKEN,
     REF_STRING_TOKEN, STRING_TOKEN, UNREFERENCED_DATE_TOKEN, UNREFERENCED_FLOAT_TOKEN,
     UNREFERENCED_INTEGER_TOKEN, UNREFERENCED_LP_DATE_TOKEN, UNREFERENCED_STRING_TOKEN,
 };
+use crate::content::{Content, ContentSerializer};
+use crate::date::scan_rfc3339;
 use crate::error::{Error, Result};
-use crate::value::{Number, Value};
-use chrono::DateTime;
-use indexmap::IndexMap;
-use serde::ser::{self, Serialize};
-use std::cell::RefCell;
-use std::rc::Rc;
+use crate::value::{Map, Number, Value};
+use alloc::rc::Rc;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use core::cell::RefCell;
+use core::fmt;
+use serde::ser::{self, Impossible, Serialize};
 
 struct InvertedIndex {
-    integers: IndexMap<i64, String>,
-    floats: IndexMap<String, String>,
-    strings: IndexMap<String, String>,
-    dates: IndexMap<String, String>,
-    lp_dates: IndexMap<String, String>,
+    integers: Map<i128, String>,
+    floats: Map<String, String>,
+    strings: Map<String, String>,
+    dates: Map<i64, String>,
+    lp_dates: Map<i64, String>,
 }
 
 impl InvertedIndex {
     fn new() -> Self {
         InvertedIndex {
-            integers: IndexMap::new(),
-            floats: IndexMap::new(),
-            strings: IndexMap::new(),
-            dates: IndexMap::new(),
-            lp_dates: IndexMap::new(),
+            integers: Map::new(),
+            floats: Map::new(),
+            strings: Map::new(),
+            dates: Map::new(),
+            lp_dates: Map::new(),
         }
     }
 }
 
+#[derive(Clone, Default)]
+pub struct SerializerOptions {
+    /// How many decimals floats are written with.
+    pub float_precision: FloatPrecision,
+    /// Write UTC timestamp strings as date tokens. Only timestamps that decode back to the
+    /// same text, like `2022-02-24T04:31:00.123Z`, are converted.
+    pub detect_utc_timestamps: bool,
+    /// Together with `detect_utc_timestamps`, also write timestamp strings with a UTC offset
+    /// as date tokens. They are read back as UTC timestamps.
+    /// Ignored when `timestamp_detector` is set, use
+    /// [`Rfc3339TimestampDetector::normalize_offsets`] there instead.
+    pub normalize_timestamp_offsets: bool,
+    /// Write structs as arrays of their field values in declaration order.
+    /// Only readable by serde_zipson, and fields can't be skipped in this mode.
+    /// The content of adjacently tagged struct variants stays a map, since serde
+    /// only reads it from one.
+    pub compact_structs: bool,
+    /// Write enum variants by their index instead of their name.
+    /// Only readable by serde_zipson.
+    pub compact_variants: bool,
+    /// Write map entries sorted by key, so equal maps always produce the same output.
+    /// Sequences keep the order they are iterated in, serde can't tell a `HashSet`
+    /// from a `Vec`, so unordered sets need to be sorted first, e.g. into a `BTreeSet`.
+    pub canonical: bool,
+    /// Write floats without a fractional part as integers, like zipson JS does.
+    /// Floats outside of `Number.MAX_SAFE_INTEGER` are still written as floats.
+    pub integral_floats_as_integers: bool,
+    /// What to write for integers JS numbers can't hold exactly.
+    pub unsafe_integers: UnsafeIntegers,
+    /// What to write for `NaN` and infinite floats, which zipson has no token for.
+    pub non_finite_floats: NonFiniteFloats,
+    /// Decides which strings `detect_utc_timestamps` writes as date tokens.
+    /// Defaults to [`Rfc3339TimestampDetector`] with `normalize_timestamp_offsets`.
+    pub timestamp_detector: Option<Arc<dyn TimestampDetector>>,
+}
+
+impl SerializerOptions {
+    // keys are only passed to custom timestamp detectors, so they aren't copied otherwise
+    fn records_keys(&self) -> bool {
+        self.detect_utc_timestamps && self.timestamp_detector.is_some()
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub enum FloatPrecision {
+    /// Round floats to 3 decimals, like zipson JS does by default.
+    #[default]
+    Reduced,
+    /// Write floats with all their decimals.
+    Full,
+    /// Round floats to 3 decimals only when that reads back as the same float,
+    /// and write them with all their decimals otherwise.
+    Auto,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub enum NonFiniteFloats {
+    /// Write them as null, like JSON.stringify does.
+    #[default]
+    Null,
+    /// Fail with [`Error::FloatMustBeFinite`].
+    Error,
+}
+
+/// Integers outside of `Number.MAX_SAFE_INTEGER` lose precision when zipson JS reads them.
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub enum UnsafeIntegers {
+    /// Write them as integers anyway.
+    #[default]
+    Allow,
+    /// Fail with [`Error::UnsafeInteger`].
+    Error,
+    /// Write them as strings.
+    String,
+    /// Write them as floats, rounded like JS would.
+    Float,
+}
+
+pub trait TimestampDetector: Send + Sync {
+    /// Returns the epoch millis to write as a date token instead of the string `v`.
+    /// `key` is the map key or struct field the string is written under, if any.
+    fn detect(&self, key: Option<&str>, v: &str) -> Option<i64>;
+}
+
+/// Detects RFC 3339 UTC timestamps that decode back to the same text.
+#[derive(Clone, Default)]
+pub struct Rfc3339TimestampDetector {
+    /// Also detect timestamps with a UTC offset. They are read back as UTC timestamps.
+    pub normalize_offsets: bool,
+}
+
+impl TimestampDetector for Rfc3339TimestampDetector {
+    fn detect(&self, _key: Option<&str>, v: &str) -> Option<i64> {
+        scan_rfc3339(v, self.normalize_offsets)
+    }
+}
+
 pub struct Serializer {
     output: String,
     // TODO: pass serializer to SerializeSeq and get rid of Rc<RefCell<_>>
     index: Rc<RefCell<InvertedIndex>>,
-    full_precision_floats: bool,
-    detect_utc_timestamps: bool,
+    options: SerializerOptions,
+    // map key or struct field the current value is written under
+    key: Option<String>,
+    // enum and variant of the last unit variant written
+    unit_variant: Option<(&'static str, &'static str)>,
+    // struct written as a map even in compact mode, see `SerializeSeq::content_variant`
+    map_struct: Option<&'static str>,
 }
 
 impl Serializer {
-    fn new(
-        full_precision_floats: bool,
-        detect_utc_timestamps: bool,
-        index: Option<Rc<RefCell<InvertedIndex>>>,
-    ) -> Self {
+    fn new(options: SerializerOptions, index: Option<Rc<RefCell<InvertedIndex>>>) -> Self {
         Serializer {
             output: String::new(),
             index: if let Some(index) = index {
@@ -59,19 +164,26 @@ impl Serializer {
             } else {
                 Rc::new(RefCell::new(InvertedIndex::new()))
             },
-            full_precision_floats,
-            detect_utc_timestamps,
+            options,
+            key: None,
+            unit_variant: None,
+            map_struct: None,
         }
     }
 
     fn serialize_integer(v: i64) -> Result<String> {
+        Self::serialize_wide_integer(i128::from(v))
+    }
+
+    // wide enough for both i64 and u64 values
+    fn serialize_wide_integer(v: i128) -> Result<String> {
         if v == 0 {
             return Ok('0'.into());
         }
 
         let mut result = String::new();
 
-        let mut modulus = if v < 0 { -v } else { v };
+        let mut modulus = v.unsigned_abs();
         let mut current;
 
         while modulus > 0 {
@@ -88,96 +200,211 @@ impl Serializer {
     }
 
     fn serialize_float(&self, v: f64) -> Result<String> {
-        if self.full_precision_floats {
-            let v_string = v.to_string();
-            let split: Vec<&str> = v_string.split('.').collect();
-            let operator = if split[0] == "-0" && split.len() > 1 {
-                "-"
+        if self.options.float_precision == FloatPrecision::Full {
+            return Self::serialize_float_full(v);
+        }
+        if v.abs() >= FLOAT_EXPONENT_UPPER_BOUND {
+            return Self::serialize_float_exponent(v);
+        }
+
+        // both parts keep the sign, so -0.5 is written as `0.-84` like zipson JS does
+        let integer = v as i64;
+        let fraction = js_round((v % 1.) * FLOAT_COMPRESSION_PRECISION) as i64;
+
+        // in auto mode the reduced form is only used when it reads back as the same float
+        if self.options.float_precision == FloatPrecision::Auto
+            && integer as f64 + fraction as f64 / FLOAT_COMPRESSION_PRECISION != v
+        {
+            return Self::serialize_float_full(v);
+        }
+
+        Ok([
+            Self::serialize_integer(integer)?,
+            FLOAT_REDUCED_PRECISION_DELIMITER.to_string(),
+            Self::serialize_integer(fraction)?,
+        ]
+        .join(""))
+    }
+
+    fn serialize_float_full(v: f64) -> Result<String> {
+        let abs = v.abs();
+        if abs >= FLOAT_EXPONENT_UPPER_BOUND || (abs != 0. && abs < FLOAT_EXPONENT_LOWER_BOUND) {
+            return Self::serialize_float_exponent(v);
+        }
+
+        let v_string = v.to_string();
+        let split: Vec<&str> = v_string.split('.').collect();
+        let operator = if split[0] == "-0" && split.len() > 1 {
+            "-"
+        } else {
+            ""
+        };
+        Ok([
+            operator.to_string(),
+            Self::serialize_integer(split[0].parse::<i64>().map_err(ser::Error::custom)?)?,
+            FLOAT_FULL_PRECISION_DELIMITER.to_string(),
+            if split.len() > 1 {
+                split[1].to_string()
             } else {
-                ""
+                '0'.to_string()
+            },
+        ]
+        .join(""))
+    }
+
+    // written the way JS Number#toString splits on `.`, so `1.5e-10` becomes `1,5e-10`
+    fn serialize_float_exponent(v: f64) -> Result<String> {
+        let v_string = format!("{:e}", v);
+        let (mantissa, exponent) = v_string.split_once('e').unwrap_or((&v_string, "0"));
+        let (integer, fraction) = mantissa.split_once('.').unwrap_or((mantissa, "0"));
+        let exponent_sign = if exponent.starts_with('-') { "" } else { "+" };
+
+        Ok([
+            Self::serialize_integer(integer.parse::<i64>().map_err(ser::Error::custom)?)?,
+            FLOAT_FULL_PRECISION_DELIMITER.to_string(),
+            fraction.to_string(),
+            "e".to_string(),
+            exponent_sign.to_string(),
+            exponent.to_string(),
+        ]
+        .join(""))
+    }
+
+    fn serialize_integer_token(&mut self, v: i128) -> Result<()> {
+        if !(-MAX_SAFE_INTEGER..=MAX_SAFE_INTEGER).contains(&v) {
+            match self.options.unsafe_integers {
+                UnsafeIntegers::Allow => {}
+                UnsafeIntegers::Error => return Err(Error::UnsafeInteger),
+                UnsafeIntegers::String => return self.serialize_string(&v.to_string()),
+                UnsafeIntegers::Float => return self.serialize_float_token(v as f64),
+            }
+        }
+
+        self.serialize_indexed_integer(v)
+    }
+
+    fn serialize_indexed_integer(&mut self, v: i128) -> Result<()> {
+        if self.try_index_integer(&v) {
+            return Ok(());
+        }
+
+        let res = Serializer::serialize_wide_integer(v)?;
+        let index = Serializer::serialize_integer(self.get_integers_len() as i64)?;
+
+        if index.chars().collect::<Vec<_>>().len() < res.chars().collect::<Vec<_>>().len() {
+            self.add_integer(v, index);
+            self.output.push(INTEGER_TOKEN);
+            self.output += &res;
+        } else {
+            self.output.push(UNREFERENCED_INTEGER_TOKEN);
+            self.output += &res;
+        }
+        Ok(())
+    }
+
+    fn serialize_float_token(&mut self, v: f64) -> Result<()> {
+        let res = self.serialize_float(v)?;
+        self.serialize_float_text(res)
+    }
+
+    // decimal text like `-12.50` or `1.5e-10`, written exactly in the full precision form,
+    // or as an integer without a fraction so the scale stays 0
+    fn serialize_decimal(&mut self, v: &str) -> Result<()> {
+        let Some((integer, fraction)) = v.split_once('.') else {
+            let v = v.parse::<i128>().map_err(ser::Error::custom)?;
+            // decimals are exact whatever `unsafe_integers` says, like their fractions
+            return match i64::try_from(v) {
+                Ok(v) if (-MAX_SAFE_INTEGER..=MAX_SAFE_INTEGER).contains(&i128::from(v)) => {
+                    ser::Serializer::serialize_i64(self, v)
+                }
+                _ => self.serialize_indexed_integer(v),
             };
-            Ok([
+        };
+        let operator = if integer == "-0" { "-" } else { "" };
+        let integer = integer.parse::<i128>().map_err(ser::Error::custom)?;
+        let is_digits = |v: &str| !v.is_empty() && v.bytes().all(|ch| ch.is_ascii_digit());
+        let is_valid = match fraction.split_once('e') {
+            Some((digits, exponent)) => {
+                is_digits(digits)
+                    && is_digits(exponent.strip_prefix(['+', '-']).unwrap_or(exponent))
+            }
+            None => is_digits(fraction),
+        };
+        if !is_valid {
+            return Err(ser::Error::custom(
+                "decimal must be written as plain decimal text",
+            ));
+        }
+
+        self.serialize_float_text(
+            [
                 operator.to_string(),
-                Self::serialize_integer(split[0].parse::<i64>().unwrap())?,
+                Self::serialize_wide_integer(integer)?,
                 FLOAT_FULL_PRECISION_DELIMITER.to_string(),
-                if split.len() > 1 {
-                    split[1].to_string()
-                } else {
-                    '0'.to_string()
-                },
+                fraction.to_string(),
             ]
-            .join(""))
-        } else {
-            let v_string = v.to_string();
-            let split: Vec<&str> = v_string.split('.').collect();
-            let integer = if split[0] == "-0" {
-                0
-            } else {
-                split[0].parse::<i64>().unwrap()
-            };
-            let fraction = ((v % 1.) * FLOAT_COMPRESSION_PRECISION).round() as i64;
+            .join(""),
+        )
+    }
 
-            Ok([
-                Self::serialize_integer(integer)?,
-                FLOAT_REDUCED_PRECISION_DELIMITER.to_string(),
-                Self::serialize_integer(fraction)?,
-            ]
-            .join(""))
+    fn serialize_float_text(&mut self, res: String) -> Result<()> {
+        if self.try_index_float(&res) {
+            return Ok(());
         }
-    }
 
-    fn serialize_date(&mut self, v: &str) -> Result<()> {
-        let date_result = DateTime::parse_from_rfc3339(v);
+        let index = Serializer::serialize_integer(self.get_floats_len() as i64)?;
 
-        match date_result {
-            Ok(date) => {
-                let millis = date.timestamp_millis();
+        if index.chars().collect::<Vec<_>>().len() < res.chars().collect::<Vec<_>>().len() {
+            self.add_float(res.clone(), index);
+            self.output.push(FLOAT_TOKEN);
+            self.output += &res;
+        } else {
+            self.output.push(UNREFERENCED_FLOAT_TOKEN);
+            self.output += &res;
+        }
 
-                let low_precision_date = millis as f64 / DATE_LOW_PRECISION;
-                let is_low_precision = low_precision_date % 1_f64 == 0_f64;
+        Ok(())
+    }
 
-                if is_low_precision {
-                    if self.try_index_lp_date(v) {
-                        return Ok(());
-                    }
+    fn serialize_date_millis(&mut self, millis: i64) -> Result<()> {
+        let low_precision_date = millis as f64 / DATE_LOW_PRECISION;
+        let is_low_precision = low_precision_date % 1_f64 == 0_f64;
 
-                    let res = Self::serialize_integer(low_precision_date as i64)?;
-                    let index = Self::serialize_integer(self.get_lp_dates_len() as i64)?;
+        if is_low_precision {
+            if self.try_index_lp_date(millis) {
+                return Ok(());
+            }
 
-                    if index.chars().collect::<Vec<_>>().len()
-                        < res.chars().collect::<Vec<_>>().len()
-                    {
-                        self.add_lp_date(v.to_string(), index);
-                        self.output.push(LP_DATE_TOKEN);
-                        self.output += &res;
-                    } else {
-                        self.output.push(UNREFERENCED_LP_DATE_TOKEN);
-                        self.output += &res;
-                    }
-                } else {
-                    if self.try_index_date(v) {
-                        return Ok(());
-                    }
-
-                    let res = Self::serialize_integer(millis)?;
-                    let index = Self::serialize_integer(self.get_dates_len() as i64)?;
-
-                    if index.chars().collect::<Vec<_>>().len()
-                        < res.chars().collect::<Vec<_>>().len()
-                    {
-                        self.add_date(v.to_string(), index);
-                        self.output.push(DATE_TOKEN);
-                        self.output += &res;
-                    } else {
-                        self.output.push(UNREFERENCED_DATE_TOKEN);
-                        self.output += &res;
-                    }
-                }
+            let res = Self::serialize_integer(low_precision_date as i64)?;
+            let index = Self::serialize_integer(self.get_lp_dates_len() as i64)?;
 
-                Ok(())
+            if index.chars().collect::<Vec<_>>().len() < res.chars().collect::<Vec<_>>().len() {
+                self.add_lp_date(millis, index);
+                self.output.push(LP_DATE_TOKEN);
+                self.output += &res;
+            } else {
+                self.output.push(UNREFERENCED_LP_DATE_TOKEN);
+                self.output += &res;
+            }
+        } else {
+            if self.try_index_date(millis) {
+                return Ok(());
+            }
+
+            let res = Self::serialize_integer(millis)?;
+            let index = Self::serialize_integer(self.get_dates_len() as i64)?;
+
+            if index.chars().collect::<Vec<_>>().len() < res.chars().collect::<Vec<_>>().len() {
+                self.add_date(millis, index);
+                self.output.push(DATE_TOKEN);
+                self.output += &res;
+            } else {
+                self.output.push(UNREFERENCED_DATE_TOKEN);
+                self.output += &res;
             }
-            _ => self.serialize_string(v),
         }
+
+        Ok(())
     }
 
     fn serialize_string(&mut self, v: &str) -> Result<()> {
@@ -185,8 +412,8 @@ impl Serializer {
             return Ok(());
         }
 
-        let escaped = v.replace(ESCAPE_CHARACTER, &ESCAPED_ESCAPE_CHARACTER);
-        let escaped_token = escaped.replace(STRING_TOKEN, &ESCAPED_STRING_TOKEN);
+        let escaped = v.replace(ESCAPE_CHARACTER, ESCAPED_ESCAPE_CHARACTER);
+        let escaped_token = escaped.replace(STRING_TOKEN, ESCAPED_STRING_TOKEN);
         let index = Self::serialize_integer(self.get_strings_len() as i64)?;
 
         if index.chars().collect::<Vec<_>>().len() < escaped_token.chars().collect::<Vec<_>>().len()
@@ -197,26 +424,42 @@ impl Serializer {
             self.output.push(STRING_TOKEN);
         } else {
             self.output.push(UNREFERENCED_STRING_TOKEN);
-            self.output += &escaped.replace(
-                UNREFERENCED_STRING_TOKEN,
-                &ESCAPED_UNREFERENCED_STRING_TOKEN,
-            );
+            self.output +=
+                &escaped.replace(UNREFERENCED_STRING_TOKEN, ESCAPED_UNREFERENCED_STRING_TOKEN);
             self.output.push(UNREFERENCED_STRING_TOKEN);
         }
 
         Ok(())
     }
 
-    fn add_integer(&self, key: i64, value: String) {
+    fn serialize_variant_start(&mut self, variant_index: u32, variant: &str) -> Result<()> {
+        if self.options.compact_variants {
+            self.output.push(ARRAY_START_TOKEN);
+            ser::Serializer::serialize_u32(self, variant_index)
+        } else {
+            self.output.push(OBJECT_START_TOKEN);
+            ser::Serializer::serialize_str(self, variant)
+        }
+    }
+
+    fn serialize_variant_end(&mut self) {
+        self.output.push(if self.options.compact_variants {
+            ARRAY_END_TOKEN
+        } else {
+            OBJECT_END_TOKEN
+        });
+    }
+
+    fn add_integer(&self, key: i128, value: String) {
         self.index.borrow_mut().integers.insert(key, value);
     }
-    fn try_index_integer(&mut self, key: &i64) -> bool {
+    fn try_index_integer(&mut self, key: &i128) -> bool {
         let index = self.index.borrow();
         let found_ref = index.integers.get(key);
 
         if let Some(found) = found_ref {
             self.output.push(REF_INTEGER_TOKEN);
-            self.output += &found;
+            self.output += found;
             true
         } else {
             false
@@ -234,7 +477,7 @@ impl Serializer {
 
         if let Some(found) = found_ref {
             self.output.push(REF_FLOAT_TOKEN);
-            self.output += &found;
+            self.output += found;
             true
         } else {
             false
@@ -252,7 +495,7 @@ impl Serializer {
         let found_ref = index.strings.get(key);
         if let Some(found) = found_ref {
             self.output.push(REF_STRING_TOKEN);
-            self.output += &found;
+            self.output += found;
             true
         } else {
             false
@@ -261,16 +504,16 @@ impl Serializer {
     fn get_strings_len(&self) -> usize {
         self.index.borrow().strings.len()
     }
-    fn add_date(&self, key: String, value: String) {
+    fn add_date(&self, key: i64, value: String) {
         self.index.borrow_mut().dates.insert(key, value);
     }
-    fn try_index_date(&mut self, key: &str) -> bool {
+    fn try_index_date(&mut self, key: i64) -> bool {
         let index = self.index.borrow();
 
-        let found_ref = index.dates.get(key);
+        let found_ref = index.dates.get(&key);
         if let Some(found) = found_ref {
             self.output.push(REF_DATE_TOKEN);
-            self.output += &found;
+            self.output += found;
             true
         } else {
             false
@@ -279,16 +522,16 @@ impl Serializer {
     fn get_dates_len(&self) -> usize {
         self.index.borrow().dates.len()
     }
-    fn add_lp_date(&self, key: String, value: String) {
+    fn add_lp_date(&self, key: i64, value: String) {
         self.index.borrow_mut().lp_dates.insert(key, value);
     }
-    fn try_index_lp_date(&mut self, key: &str) -> bool {
+    fn try_index_lp_date(&mut self, key: i64) -> bool {
         let index = self.index.borrow();
 
-        let found_ref = index.lp_dates.get(key);
+        let found_ref = index.lp_dates.get(&key);
         if let Some(found) = found_ref {
             self.output.push(REF_LP_DATE_TOKEN);
-            self.output += &found;
+            self.output += found;
             true
         } else {
             false
@@ -338,22 +581,8 @@ impl<'a> ser::Serializer for &'a mut Serializer {
                 .push(INTEGER_SMALL_TOKENS[(v + INTEGER_SMALL_TOKEN_ELEMENT_OFFSET) as usize]);
             return Ok(());
         }
-        if self.try_index_integer(&v) {
-            return Ok(());
-        }
 
-        let res = Serializer::serialize_integer(v)?;
-        let index = Serializer::serialize_integer(self.get_integers_len() as i64)?;
-
-        if index.chars().collect::<Vec<_>>().len() < res.chars().collect::<Vec<_>>().len() {
-            self.add_integer(v, index);
-            self.output.push(INTEGER_TOKEN);
-            self.output += &res;
-        } else {
-            self.output.push(UNREFERENCED_INTEGER_TOKEN);
-            self.output += &res;
-        }
-        Ok(())
+        self.serialize_integer_token(i128::from(v))
     }
 
     fn serialize_u8(self, v: u8) -> Result<()> {
@@ -369,32 +598,35 @@ impl<'a> ser::Serializer for &'a mut Serializer {
     }
 
     fn serialize_u64(self, v: u64) -> Result<()> {
-        self.serialize_i64(v as i64)
+        match i64::try_from(v) {
+            Ok(v) => self.serialize_i64(v),
+            Err(_) => self.serialize_integer_token(i128::from(v)),
+        }
     }
 
     fn serialize_f32(self, v: f32) -> Result<()> {
-        self.serialize_f64(f64::from(v))
+        // widen through the shortest f32 text, so 0.1f32 is written as 0.1
+        // and not as 0.10000000149011612
+        let widened = v.to_string().parse::<f64>().unwrap_or(f64::from(v));
+        self.serialize_f64(widened)
     }
 
     fn serialize_f64(self, v: f64) -> Result<()> {
-        let res = self.serialize_float(v)?;
-
-        if self.try_index_float(&res) {
-            return Ok(());
+        if !v.is_finite() {
+            return match self.options.non_finite_floats {
+                NonFiniteFloats::Null => self.serialize_unit(),
+                NonFiniteFloats::Error => Err(Error::FloatMustBeFinite),
+            };
         }
-
-        let index = Serializer::serialize_integer(self.get_floats_len() as i64)?;
-
-        if index.chars().collect::<Vec<_>>().len() < res.chars().collect::<Vec<_>>().len() {
-            self.add_float(res.clone(), index);
-            self.output.push(FLOAT_TOKEN);
-            self.output += &res;
-        } else {
-            self.output.push(UNREFERENCED_FLOAT_TOKEN);
-            self.output += &res;
+        // larger floats stay floats, the unsafe integer policy is only for integers
+        if self.options.integral_floats_as_integers
+            && v % 1. == 0.
+            && v.abs() <= MAX_SAFE_INTEGER as f64
+        {
+            return self.serialize_i64(v as i64);
         }
 
-        Ok(())
+        self.serialize_float_token(v)
     }
 
     fn serialize_char(self, v: char) -> Result<()> {
@@ -402,8 +634,19 @@ impl<'a> ser::Serializer for &'a mut Serializer {
     }
 
     fn serialize_str(self, v: &str) -> Result<()> {
-        if self.detect_utc_timestamps && DATE_REGEX.is_match(v) {
-            return self.serialize_date(v);
+        if self.options.detect_utc_timestamps {
+            let key = self.key.as_deref();
+            let millis = match &self.options.timestamp_detector {
+                Some(detector) => detector.detect(key, v),
+                None => Rfc3339TimestampDetector {
+                    normalize_offsets: self.options.normalize_timestamp_offsets,
+                }
+                .detect(key, v),
+            };
+
+            if let Some(millis) = millis {
+                return self.serialize_date_millis(millis);
+            }
         }
 
         self.serialize_string(v)
@@ -435,34 +678,65 @@ impl<'a> ser::Serializer for &'a mut Serializer {
 
     fn serialize_unit_variant(
         self,
-        _name: &'static str,
-        _variant_index: u32,
+        name: &'static str,
+        variant_index: u32,
         variant: &'static str,
     ) -> Result<()> {
-        self.serialize_str(variant)
+        self.unit_variant = Some((name, variant));
+        if self.options.compact_variants {
+            self.serialize_u32(variant_index)
+        } else {
+            self.serialize_str(variant)
+        }
     }
 
-    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<()>
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<()>
     where
         T: ?Sized + Serialize,
     {
+        if name == DATE_NEWTYPE_NAME {
+            return match value.serialize(ContentSerializer)? {
+                Content::I64(millis) => self.serialize_date_millis(millis),
+                _ => Err(ser::Error::custom(
+                    "date must be serialized as epoch millis",
+                )),
+            };
+        }
+        if name == DECIMAL_NEWTYPE_NAME {
+            return match value.serialize(ContentSerializer)? {
+                Content::String(v) => self.serialize_decimal(&v),
+                _ => Err(ser::Error::custom("decimal must be serialized as a string")),
+            };
+        }
+        if name == FLOAT_REDUCED_NEWTYPE_NAME || name == FLOAT_FULL_NEWTYPE_NAME {
+            let options = self.options.clone();
+            self.options.float_precision = if name == FLOAT_FULL_NEWTYPE_NAME {
+                FloatPrecision::Full
+            } else {
+                FloatPrecision::Reduced
+            };
+            self.options.integral_floats_as_integers = false;
+            let res = value.serialize(&mut *self);
+            self.options = options;
+            return res;
+        }
+
         value.serialize(self)
     }
 
     fn serialize_newtype_variant<T>(
         self,
         _name: &'static str,
-        _variant_index: u32,
+        variant_index: u32,
         variant: &'static str,
         value: &T,
     ) -> Result<()>
     where
         T: ?Sized + Serialize,
     {
-        self.output.push(OBJECT_START_TOKEN);
-        variant.serialize(&mut *self)?;
+        self.serialize_variant_start(variant_index, variant)?;
         value.serialize(&mut *self)?;
-        self.output.push(OBJECT_END_TOKEN);
+        self.serialize_variant_end();
         Ok(())
     }
 
@@ -471,10 +745,13 @@ impl<'a> ser::Serializer for &'a mut Serializer {
         Ok(SerializeSeq {
             output: &mut self.output,
             index: self.index.clone(),
-            full_precision_floats: self.full_precision_floats,
-            detect_utc_timestamps: self.detect_utc_timestamps,
+            options: self.options.clone(),
             last_value: None,
             repeat_count: 0,
+            map_key: None,
+            map_entries: vec![],
+            struct_name: None,
+            content_variant: None,
         })
     }
 
@@ -493,12 +770,11 @@ impl<'a> ser::Serializer for &'a mut Serializer {
     fn serialize_tuple_variant(
         self,
         _name: &'static str,
-        _variant_index: u32,
+        variant_index: u32,
         variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeTupleVariant> {
-        self.output.push(OBJECT_START_TOKEN);
-        variant.serialize(&mut *self)?;
+        self.serialize_variant_start(variant_index, variant)?;
         self.output.push(ARRAY_START_TOKEN);
         Ok(self)
     }
@@ -508,27 +784,39 @@ impl<'a> ser::Serializer for &'a mut Serializer {
         Ok(SerializeSeq {
             output: &mut self.output,
             index: self.index.clone(),
-            full_precision_floats: self.full_precision_floats,
-            detect_utc_timestamps: self.detect_utc_timestamps,
+            options: self.options.clone(),
             last_value: None,
             repeat_count: 0,
+            map_key: None,
+            map_entries: vec![],
+            struct_name: None,
+            content_variant: None,
         })
     }
 
-    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Self::SerializeStruct> {
-        self.serialize_map(Some(len))
+    fn serialize_struct(self, name: &'static str, len: usize) -> Result<Self::SerializeStruct> {
+        if self.options.compact_structs && self.map_struct != Some(name) {
+            let mut seq = self.serialize_seq(Some(len))?;
+            seq.struct_name = Some(name);
+            Ok(seq)
+        } else {
+            self.serialize_map(Some(len))
+        }
     }
 
     fn serialize_struct_variant(
         self,
         _name: &'static str,
-        _variant_index: u32,
+        variant_index: u32,
         variant: &'static str,
         _len: usize,
     ) -> Result<Self::SerializeStructVariant> {
-        self.output.push(OBJECT_START_TOKEN);
-        variant.serialize(&mut *self)?;
-        self.output.push(OBJECT_START_TOKEN);
+        self.serialize_variant_start(variant_index, variant)?;
+        self.output.push(if self.options.compact_structs {
+            ARRAY_START_TOKEN
+        } else {
+            OBJECT_START_TOKEN
+        });
         Ok(self)
     }
 }
@@ -536,11 +824,20 @@ impl<'a> ser::Serializer for &'a mut Serializer {
 pub struct SerializeSeq<'a> {
     output: &'a mut String,
     index: Rc<RefCell<InvertedIndex>>,
-    full_precision_floats: bool,
-    detect_utc_timestamps: bool,
+    options: SerializerOptions,
 
     last_value: Option<String>,
     repeat_count: i64,
+
+    map_key: Option<String>,
+    map_entries: Vec<(String, Content)>,
+
+    // name of the compact struct being written
+    struct_name: Option<&'static str>,
+    // adjacently tagged enums are written as a struct named after the enum, holding a
+    // unit variant of it as the tag and then the variant content. Struct variant
+    // content is only read back from a map, so it's written as one.
+    content_variant: Option<&'static str>,
 }
 
 impl SerializeSeq<'_> {
@@ -550,13 +847,11 @@ impl SerializeSeq<'_> {
                 self.output.push_str(last_value);
             } else if self.repeat_count < ARRAY_REPEAT_COUNT_THRESHOLD {
                 self.output.push(ARRAY_REPEAT_TOKEN);
-            } else if self.repeat_count >= ARRAY_REPEAT_COUNT_THRESHOLD {
-                if !is_repeat {
-                    self.output.push(ARRAY_REPEAT_MANY_TOKEN);
-                    self.output.push_str(&Serializer::serialize_integer(
-                        self.repeat_count - ARRAY_REPEAT_COUNT_THRESHOLD + 1,
-                    )?);
-                }
+            } else if self.repeat_count >= ARRAY_REPEAT_COUNT_THRESHOLD && !is_repeat {
+                self.output.push(ARRAY_REPEAT_MANY_TOKEN);
+                self.output.push_str(&Serializer::serialize_integer(
+                    self.repeat_count - ARRAY_REPEAT_COUNT_THRESHOLD + 1,
+                )?);
             }
         }
 
@@ -564,7 +859,7 @@ impl SerializeSeq<'_> {
     }
 }
 
-impl<'a> ser::SerializeSeq for SerializeSeq<'_> {
+impl ser::SerializeSeq for SerializeSeq<'_> {
     type Ok = ();
     type Error = Error;
 
@@ -573,13 +868,23 @@ impl<'a> ser::SerializeSeq for SerializeSeq<'_> {
         T: ?Sized + Serialize,
     {
         // TODO: implement to_value serializer and compare values instead of strings
-        let value_string = to_string_nested(
-            &value,
-            self.full_precision_floats,
-            self.detect_utc_timestamps,
-            self.index.clone(),
-        )?;
+        let value_string = to_string_nested(&value, None, &self.options, self.index.clone())?;
+        self.push_element(value_string)
+    }
+
+    fn end(mut self) -> Result<()> {
+        self.handle_last_value(false)?;
+
+        self.last_value = None;
+        self.repeat_count = 0;
 
+        self.output.push(ARRAY_END_TOKEN);
+        Ok(())
+    }
+}
+
+impl SerializeSeq<'_> {
+    fn push_element(&mut self, value_string: String) -> Result<()> {
         match self.last_value {
             None => {
                 // first array element
@@ -604,51 +909,41 @@ impl<'a> ser::SerializeSeq for SerializeSeq<'_> {
 
         Ok(())
     }
-
-    fn end(mut self) -> Result<()> {
-        self.handle_last_value(false)?;
-
-        self.last_value = None;
-        self.repeat_count = 0;
-
-        self.output.push(ARRAY_END_TOKEN);
-        Ok(())
-    }
 }
 
-impl<'a> ser::SerializeTuple for SerializeSeq<'_> {
+impl ser::SerializeTuple for SerializeSeq<'_> {
     type Ok = ();
     type Error = Error;
 
-    fn serialize_element<T>(&mut self, value: &T) -> std::result::Result<(), Self::Error>
+    fn serialize_element<T>(&mut self, value: &T) -> core::result::Result<(), Self::Error>
     where
         T: ?Sized + Serialize,
     {
         ser::SerializeSeq::serialize_element(self, value)
     }
 
-    fn end(self) -> std::result::Result<Self::Ok, Self::Error> {
+    fn end(self) -> core::result::Result<Self::Ok, Self::Error> {
         ser::SerializeSeq::end(self)
     }
 }
 
-impl<'a> ser::SerializeTupleStruct for SerializeSeq<'_> {
+impl ser::SerializeTupleStruct for SerializeSeq<'_> {
     type Ok = ();
     type Error = Error;
 
-    fn serialize_field<T>(&mut self, value: &T) -> std::result::Result<(), Self::Error>
+    fn serialize_field<T>(&mut self, value: &T) -> core::result::Result<(), Self::Error>
     where
         T: ?Sized + Serialize,
     {
         ser::SerializeSeq::serialize_element(self, value)
     }
 
-    fn end(self) -> std::result::Result<Self::Ok, Self::Error> {
+    fn end(self) -> core::result::Result<Self::Ok, Self::Error> {
         ser::SerializeSeq::end(self)
     }
 }
 
-impl<'a> ser::SerializeTupleVariant for &'a mut Serializer {
+impl ser::SerializeTupleVariant for &mut Serializer {
     type Ok = ();
     type Error = Error;
 
@@ -661,12 +956,12 @@ impl<'a> ser::SerializeTupleVariant for &'a mut Serializer {
 
     fn end(self) -> Result<()> {
         self.output.push(ARRAY_END_TOKEN);
-        self.output.push(OBJECT_END_TOKEN);
+        self.serialize_variant_end();
         Ok(())
     }
 }
 
-impl<'a> ser::SerializeMap for SerializeSeq<'_> {
+impl ser::SerializeMap for SerializeSeq<'_> {
     type Ok = ();
     type Error = Error;
 
@@ -674,13 +969,15 @@ impl<'a> ser::SerializeMap for SerializeSeq<'_> {
     where
         T: ?Sized + Serialize,
     {
-        let key_string = to_string_nested(
-            &key,
-            self.full_precision_floats,
-            self.detect_utc_timestamps,
-            self.index.clone(),
-        )?;
-        self.output.push_str(&key_string);
+        let key = key.serialize(MapKeySerializer)?;
+
+        // in canonical mode entries are written sorted on end,
+        // so references get assigned in key order
+        if !self.options.canonical {
+            let key_string = to_string_key(&key, &self.options, self.index.clone())?;
+            self.output.push_str(&key_string);
+        }
+        self.map_key = Some(key);
 
         Ok(())
     }
@@ -689,24 +986,38 @@ impl<'a> ser::SerializeMap for SerializeSeq<'_> {
     where
         T: ?Sized + Serialize,
     {
-        let value_string = to_string_nested(
-            &value,
-            self.full_precision_floats,
-            self.detect_utc_timestamps,
-            self.index.clone(),
-        )?;
+        let key = self.map_key.take().unwrap_or_default();
+
+        if self.options.canonical {
+            self.map_entries
+                .push((key, value.serialize(ContentSerializer)?));
+            return Ok(());
+        }
+
+        let value_string = to_string_nested(&value, Some(&key), &self.options, self.index.clone())?;
         self.output.push_str(&value_string);
 
         Ok(())
     }
 
-    fn end(self) -> Result<()> {
+    fn end(mut self) -> Result<()> {
+        let mut map_entries = core::mem::take(&mut self.map_entries);
+        map_entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        for (key, value) in map_entries {
+            let key_string = to_string_key(&key, &self.options, self.index.clone())?;
+            self.output.push_str(&key_string);
+            let value_string =
+                to_string_nested(&value, Some(&key), &self.options, self.index.clone())?;
+            self.output.push_str(&value_string);
+        }
+
         self.output.push(OBJECT_END_TOKEN);
         Ok(())
     }
 }
 
-impl<'a> ser::SerializeStruct for SerializeSeq<'_> {
+impl ser::SerializeStruct for SerializeSeq<'_> {
     type Ok = ();
     type Error = Error;
 
@@ -714,31 +1025,51 @@ impl<'a> ser::SerializeStruct for SerializeSeq<'_> {
     where
         T: ?Sized + Serialize,
     {
-        let field_string = to_string_nested(
-            &key,
-            self.full_precision_floats,
-            self.detect_utc_timestamps,
-            self.index.clone(),
-        )?;
+        if self.struct_name.is_some() {
+            let mut serializer = Serializer::new(self.options.clone(), Some(self.index.clone()));
+            if self.options.records_keys() {
+                serializer.key = Some(key.to_string());
+            }
+            serializer.map_struct = self.content_variant.take();
+            value.serialize(&mut serializer)?;
+
+            self.content_variant = serializer
+                .unit_variant
+                .filter(|(name, _)| self.struct_name == Some(name))
+                .map(|(_, variant)| variant);
+            return self.push_element(serializer.output);
+        }
+
+        let field_string = to_string_key(key, &self.options, self.index.clone())?;
         self.output.push_str(&field_string);
-        let value_string = to_string_nested(
-            &value,
-            self.full_precision_floats,
-            self.detect_utc_timestamps,
-            self.index.clone(),
-        )?;
+        let value_string = to_string_nested(&value, Some(key), &self.options, self.index.clone())?;
         self.output.push_str(&value_string);
 
         Ok(())
     }
 
+    fn skip_field(&mut self, key: &'static str) -> Result<()> {
+        if self.struct_name.is_some() {
+            return Err(ser::Error::custom(format!(
+                "field `{}` can't be skipped in compact struct mode",
+                key
+            )));
+        }
+
+        Ok(())
+    }
+
     fn end(self) -> Result<()> {
+        if self.struct_name.is_some() {
+            return ser::SerializeSeq::end(self);
+        }
+
         self.output.push(OBJECT_END_TOKEN);
         Ok(())
     }
 }
 
-impl<'a> ser::SerializeStructVariant for &'a mut Serializer {
+impl ser::SerializeStructVariant for &mut Serializer {
     type Ok = ();
     type Error = Error;
 
@@ -746,19 +1077,254 @@ impl<'a> ser::SerializeStructVariant for &'a mut Serializer {
     where
         T: ?Sized + Serialize,
     {
-        key.serialize(&mut **self)?;
-        value.serialize(&mut **self)
+        if !self.options.compact_structs {
+            self.serialize_string(key)?;
+        }
+        if !self.options.records_keys() {
+            return value.serialize(&mut **self);
+        }
+
+        let parent_key = self.key.replace(key.to_string());
+        let res = value.serialize(&mut **self);
+        self.key = parent_key;
+        res
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<()> {
+        if self.options.compact_structs {
+            return Err(ser::Error::custom(format!(
+                "field `{}` can't be skipped in compact struct mode",
+                key
+            )));
+        }
+
+        Ok(())
     }
 
     fn end(self) -> Result<()> {
-        self.output.push(OBJECT_END_TOKEN);
-        self.output.push(OBJECT_END_TOKEN);
+        self.output.push(if self.options.compact_structs {
+            ARRAY_END_TOKEN
+        } else {
+            OBJECT_END_TOKEN
+        });
+        self.serialize_variant_end();
         Ok(())
     }
 }
 
+// Math.round rounds halves towards positive infinity, unlike f64::round,
+// only used on fractions scaled by FLOAT_COMPRESSION_PRECISION so `as` can't saturate
+fn js_round(v: f64) -> f64 {
+    let trunc = v as i64 as f64;
+    let floor = if trunc > v { trunc - 1. } else { trunc };
+    if v - floor >= 0.5 {
+        floor + 1.
+    } else {
+        floor
+    }
+}
+
+// the shortest digits, formatted like JS Number#toString: with an exponent outside
+// of [1e-6, 1e21), e.g. `1e+21` and `1.5e-7`, and `-0` as `0`
+fn js_number_string<T>(v: T) -> String
+where
+    T: Copy + Into<f64> + fmt::Display + fmt::LowerExp,
+{
+    let abs = v.into().abs();
+    if abs == 0. {
+        '0'.to_string()
+    } else if (FLOAT_EXPONENT_LOWER_BOUND..1e21).contains(&abs) {
+        v.to_string()
+    } else {
+        let v_string = format!("{:e}", v);
+        if v_string.contains("e-") {
+            v_string
+        } else {
+            v_string.replacen('e', "e+", 1)
+        }
+    }
+}
+
+struct MapKeySerializer;
+
+fn key_must_be_a_string() -> Error {
+    Error::KeyMustBeAString
+}
+
+impl ser::Serializer for MapKeySerializer {
+    type Ok = String;
+    type Error = Error;
+
+    type SerializeSeq = Impossible<String, Error>;
+    type SerializeTuple = Impossible<String, Error>;
+    type SerializeTupleStruct = Impossible<String, Error>;
+    type SerializeTupleVariant = Impossible<String, Error>;
+    type SerializeMap = Impossible<String, Error>;
+    type SerializeStruct = Impossible<String, Error>;
+    type SerializeStructVariant = Impossible<String, Error>;
+
+    fn serialize_bool(self, v: bool) -> Result<String> {
+        Ok(if v { "true" } else { "false" }.to_string())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<String> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<String> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<String> {
+        self.serialize_i64(i64::from(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<String> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<String> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<String> {
+        self.serialize_u64(u64::from(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<String> {
+        if v.is_finite() {
+            Ok(js_number_string(v))
+        } else {
+            self.serialize_f64(f64::from(v))
+        }
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<String> {
+        // same keys JS gets from String(number)
+        if v.is_nan() {
+            Ok("NaN".to_string())
+        } else if v.is_infinite() {
+            Ok(if v > 0. { "Infinity" } else { "-Infinity" }.to_string())
+        } else {
+            Ok(js_number_string(v))
+        }
+    }
+
+    fn serialize_char(self, v: char) -> Result<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<String> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<String> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_none(self) -> Result<String> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_some<T>(self, _value: &T) -> Result<String>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_unit(self) -> Result<String> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<String> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<String> {
+        Ok(variant.to_string())
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<String>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<String>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(key_must_be_a_string())
+    }
+}
+
 impl Serialize for Value {
-    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
@@ -775,13 +1341,17 @@ impl Serialize for Value {
 }
 
 impl Serialize for Number {
-    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
         match self {
             Self::Int(v) => serializer.serialize_i64(*v),
+            Self::UInt(v) => serializer.serialize_u64(*v),
             Self::Float(v) => serializer.serialize_f64(*v),
+            // written like a decimal, so other serializers see the text
+            #[cfg(feature = "arbitrary_precision")]
+            Self::Raw(v) => serializer.serialize_newtype_struct(DECIMAL_NEWTYPE_NAME, v),
         }
     }
 }
@@ -794,21 +1364,53 @@ pub fn to_string<T>(
 where
     T: Serialize,
 {
-    let mut serializer = Serializer::new(full_precision_floats, detect_utc_timestamps, None);
+    to_string_with_options(
+        value,
+        SerializerOptions {
+            float_precision: if full_precision_floats {
+                FloatPrecision::Full
+            } else {
+                FloatPrecision::Reduced
+            },
+            detect_utc_timestamps,
+            ..SerializerOptions::default()
+        },
+    )
+}
+
+pub fn to_string_with_options<T>(value: &T, options: SerializerOptions) -> Result<String>
+where
+    T: Serialize,
+{
+    let mut serializer = Serializer::new(options, None);
     value.serialize(&mut serializer)?;
     Ok(serializer.output)
 }
 
 fn to_string_nested<T>(
     value: &T,
-    full_precision_floats: bool,
-    detect_utc_timestamps: bool,
+    key: Option<&str>,
+    options: &SerializerOptions,
     index: Rc<RefCell<InvertedIndex>>,
 ) -> Result<String>
 where
     T: Serialize,
 {
-    let mut serializer = Serializer::new(full_precision_floats, detect_utc_timestamps, Some(index));
+    let mut serializer = Serializer::new(options.clone(), Some(index));
+    if options.records_keys() {
+        serializer.key = key.map(str::to_string);
+    }
     value.serialize(&mut serializer)?;
     Ok(serializer.output)
 }
+
+fn to_string_key(
+    key: &str,
+    options: &SerializerOptions,
+    index: Rc<RefCell<InvertedIndex>>,
+) -> Result<String> {
+    let mut serializer = Serializer::new(options.clone(), Some(index));
+    // keys are always plain strings in zipson, so no date detection here
+    serializer.serialize_string(key)?;
+    Ok(serializer.output)
+}
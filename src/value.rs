@@ -1,13 +1,29 @@
+#[cfg(feature = "arbitrary_precision")]
+use crate::constants::{DECIMAL_NEWTYPE_NAME, RAW_NUMBER_KEY, VALUE_NEWTYPE_NAME};
+use crate::date::format_rfc3339;
 use crate::error::Error;
-use indexmap::IndexMap;
-use serde::de::{DeserializeSeed, Visitor};
+use alloc::string::String;
+use alloc::vec::{self, Vec};
+use serde::de::{DeserializeSeed, IntoDeserializer, Unexpected, Visitor};
 use serde::{de, forward_to_deserialize_any};
-use std::vec;
+
+/// The map behind `Value::Object`, in insertion order with the `preserve_order` feature
+/// and sorted by key without it.
+#[cfg(feature = "preserve_order")]
+pub type Map<K, V> = indexmap::IndexMap<K, V>;
+#[cfg(not(feature = "preserve_order"))]
+pub type Map<K, V> = alloc::collections::BTreeMap<K, V>;
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum Number {
     Int(i64),
+    /// Only used for integers above `i64::MAX`.
+    UInt(u64),
     Float(f64),
+    /// Full precision floats and integers beyond `u64` as decimal text, e.g. `12.50`,
+    /// written back exactly. Only used with the `arbitrary_precision` feature.
+    #[cfg(feature = "arbitrary_precision")]
+    Raw(String),
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -18,7 +34,66 @@ pub enum Value {
     Number(Number),
     String(String),
     Array(Vec<Value>),
-    Object(IndexMap<String, Value>),
+    Object(Map<String, Value>),
+}
+
+// floats without a fractional part are accepted by integer targets
+macro_rules! deserialize_coerced_integer {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                match self {
+                    Value::Number(Number::Float(v)) => visit_integral_float(v, visitor),
+                    _ => self.deserialize_any(visitor),
+                }
+            }
+        )*
+    };
+}
+
+pub(crate) fn visit_wide_integer<'de, V>(v: i128, visitor: V) -> Result<V::Value, Error>
+where
+    V: Visitor<'de>,
+{
+    if let Ok(v) = i64::try_from(v) {
+        visitor.visit_i64(v)
+    } else if let Ok(v) = u64::try_from(v) {
+        visitor.visit_u64(v)
+    } else {
+        // only the largest decimals need more than 64 bits
+        visitor.visit_i128(v)
+    }
+}
+
+// number text is read as an integer or float unless the target asks for it as is
+#[cfg(feature = "arbitrary_precision")]
+fn visit_raw_number<'de, V>(v: &str, visitor: V) -> Result<V::Value, Error>
+where
+    V: Visitor<'de>,
+{
+    if v.contains(['.', 'e']) {
+        visitor.visit_f64(v.parse().map_err(|_| Error::ExpectedFloat)?)
+    } else {
+        visit_wide_integer(v.parse().map_err(|_| Error::ExpectedInteger)?, visitor)
+    }
+}
+
+pub(crate) fn visit_integral_float<'de, V>(v: f64, visitor: V) -> Result<V::Value, Error>
+where
+    V: Visitor<'de>,
+{
+    if v % 1. != 0. {
+        Err(de::Error::invalid_type(Unexpected::Float(v), &visitor))
+    } else if v >= i64::MIN as f64 && v < i64::MAX as f64 {
+        visitor.visit_i64(v as i64)
+    } else if v >= 0. && v < u64::MAX as f64 {
+        visitor.visit_u64(v as u64)
+    } else {
+        Err(de::Error::invalid_value(Unexpected::Float(v), &visitor))
+    }
 }
 
 impl<'de> de::Deserializer<'de> for Value {
@@ -35,6 +110,9 @@ impl<'de> de::Deserializer<'de> for Value {
             Value::Number(n) => match n {
                 Number::Float(f) => visitor.visit_f64(f),
                 Number::Int(i) => visitor.visit_i64(i),
+                Number::UInt(u) => visitor.visit_u64(u),
+                #[cfg(feature = "arbitrary_precision")]
+                Number::Raw(v) => visit_raw_number(&v, visitor),
             },
             Value::String(v) => visitor.visit_string(v),
             Value::Array(v) => visitor.visit_seq(&mut SeqAccess::new(v)),
@@ -43,19 +121,104 @@ impl<'de> de::Deserializer<'de> for Value {
     }
 
     forward_to_deserialize_any! {
-        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
-        bytes byte_buf option unit unit_struct seq tuple
-        tuple_struct map struct enum identifier ignored_any
+        bool i128 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map struct ignored_any
     }
 
-    fn deserialize_newtype_struct<V>(
+    deserialize_coerced_integer! {
+        deserialize_i8
+        deserialize_i16
+        deserialize_i32
+        deserialize_i64
+        deserialize_u8
+        deserialize_u16
+        deserialize_u32
+        deserialize_u64
+    }
+
+    fn deserialize_enum<V>(
         self,
         _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::String(variant) => visitor.visit_enum(variant.into_deserializer()),
+            Value::Number(Number::Int(variant_index)) => {
+                let variant_index =
+                    u32::try_from(variant_index).map_err(|_| Error::ExpectedEnum)?;
+                visitor.visit_enum(variant_index.into_deserializer())
+            }
+            Value::Object(v) => {
+                let mut iter = v.into_iter();
+                match (iter.next(), iter.next()) {
+                    (Some((variant, value)), None) => {
+                        visitor.visit_enum(EnumAccess::new(Value::String(variant), value))
+                    }
+                    _ => Err(Error::ExpectedEnum),
+                }
+            }
+            Value::Array(v) => {
+                let mut iter = v.into_iter();
+                match (iter.next(), iter.next(), iter.next()) {
+                    (Some(variant @ Value::Number(Number::Int(_))), Some(value), None) => {
+                        visitor.visit_enum(EnumAccess::new(variant, value))
+                    }
+                    _ => Err(Error::ExpectedEnum),
+                }
+            }
+            _ => Err(Error::ExpectedEnum),
+        }
+    }
+
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Number(Number::Int(variant_index)) if variant_index >= 0 => {
+                visitor.visit_u64(variant_index as u64)
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self {
+            Value::Undefined | Value::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    #[cfg_attr(not(feature = "arbitrary_precision"), allow(unused_variables))]
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
         visitor: V,
     ) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
+        #[cfg(feature = "arbitrary_precision")]
+        if let Value::Number(Number::Raw(v)) = self {
+            return if name == VALUE_NEWTYPE_NAME {
+                visitor.visit_map(de::value::MapDeserializer::new(core::iter::once((
+                    RAW_NUMBER_KEY,
+                    v,
+                ))))
+            } else if name == DECIMAL_NEWTYPE_NAME {
+                visitor.visit_string(v)
+            } else {
+                visit_raw_number(&v, visitor)
+            };
+        }
         visitor.visit_newtype_struct(self)
     }
 }
@@ -87,12 +250,12 @@ impl<'de> de::SeqAccess<'de> for SeqAccess {
 }
 
 struct MapAccess {
-    iter: <IndexMap<String, Value> as IntoIterator>::IntoIter,
+    iter: <Map<String, Value> as IntoIterator>::IntoIter,
     value: Option<Value>,
 }
 
 impl MapAccess {
-    fn new(map: IndexMap<String, Value>) -> Self {
+    fn new(map: Map<String, Value>) -> Self {
         Self {
             iter: map.into_iter(),
             value: None,
@@ -110,7 +273,7 @@ impl<'de> de::MapAccess<'de> for MapAccess {
         match self.iter.next() {
             Some((key, value)) => {
                 self.value = Some(value);
-                let key_de = Value::String(key);
+                let key_de = MapKeyDeserializer::new(key);
                 seed.deserialize(key_de).map(Some)
             }
             None => Ok(None),
@@ -127,3 +290,266 @@ impl<'de> de::MapAccess<'de> for MapAccess {
         }
     }
 }
+
+struct EnumAccess {
+    variant: Value,
+    value: Value,
+}
+
+impl EnumAccess {
+    fn new(variant: Value, value: Value) -> Self {
+        Self { variant, value }
+    }
+}
+
+impl<'de> de::EnumAccess<'de> for EnumAccess {
+    type Error = Error;
+    type Variant = VariantAccess;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let variant = seed.deserialize(self.variant)?;
+
+        Ok((variant, VariantAccess { value: self.value }))
+    }
+}
+
+struct VariantAccess {
+    value: Value,
+}
+
+impl<'de> de::VariantAccess<'de> for VariantAccess {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        match self.value {
+            Value::Undefined | Value::Null => Ok(()),
+            _ => Err(Error::ExpectedNull),
+        }
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        seed.deserialize(self.value)
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Value::Array(v) => visitor.visit_seq(&mut SeqAccess::new(v)),
+            _ => Err(Error::ExpectedArray),
+        }
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.value {
+            Value::Object(v) => visitor.visit_map(&mut MapAccess::new(v)),
+            // compact structs are written as arrays
+            Value::Array(v) => visitor.visit_seq(&mut SeqAccess::new(v)),
+            _ => Err(Error::ExpectedMap),
+        }
+    }
+}
+
+// Object keys are always strings, so numeric and bool keys are parsed back from them
+pub(crate) struct MapKeyDeserializer {
+    key: String,
+}
+
+impl MapKeyDeserializer {
+    pub(crate) fn new(key: String) -> Self {
+        Self { key }
+    }
+}
+
+macro_rules! deserialize_parsed_key {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                match self.key.parse() {
+                    Ok(v) => visitor.$visit(v),
+                    Err(_) => Err(de::Error::invalid_value(
+                        Unexpected::Str(&self.key),
+                        &visitor,
+                    )),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for MapKeyDeserializer {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_string(self.key)
+    }
+
+    deserialize_parsed_key! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+    }
+
+    forward_to_deserialize_any! {
+        char str string bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_enum(self.key.into_deserializer())
+    }
+}
+
+// Dates are decoded as RFC 3339 strings unless the target asks for epoch millis
+// or, with the `system_time` feature, a `SystemTime`
+pub(crate) struct DateDeserializer {
+    millis: i64,
+}
+
+impl DateDeserializer {
+    pub(crate) fn new(millis: i64) -> Self {
+        Self { millis }
+    }
+
+    fn format(&self) -> Result<String, Error> {
+        format_rfc3339(self.millis).ok_or(Error::ExpectedDate)
+    }
+}
+
+impl<'de> de::Deserializer<'de> for DateDeserializer {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_string(self.format()?)
+    }
+
+    fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_i64(self.millis)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map identifier ignored_any
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if cfg!(feature = "system_time") && name == "SystemTime" {
+            let millis = u64::try_from(self.millis).map_err(|_| {
+                de::Error::invalid_value(Unexpected::Signed(self.millis), &"a date after 1970")
+            })?;
+            let mut map = de::value::MapDeserializer::new(
+                [
+                    ("secs_since_epoch", millis / 1000),
+                    ("nanos_since_epoch", millis % 1000 * 1_000_000),
+                ]
+                .into_iter(),
+            );
+            let value = visitor.visit_map(&mut map)?;
+            map.end()?;
+            return Ok(value);
+        }
+
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_enum(self.format()?.into_deserializer())
+    }
+}
@@ -1,6 +1,9 @@
 use crate::de::test_parse;
 
-use indexmap::{indexmap, IndexMap};
+use indexmap::indexmap;
+#[cfg(feature = "preserve_order")]
+use indexmap::IndexMap;
+#[cfg(feature = "preserve_order")]
 use serde::Deserialize;
 use serde_zipson::value::{Number, Value};
 
@@ -104,6 +107,8 @@ fn test_one_float_small() {
     );
 }
 
+// read as `Number::Raw` with arbitrary_precision
+#[cfg(not(feature = "arbitrary_precision"))]
 #[test]
 fn test_one_float_full_precision() {
     test_parse(
@@ -184,6 +189,7 @@ fn test_repeat_array() {
     );
 }
 
+#[cfg(feature = "preserve_order")]
 #[test]
 fn test_repeat_object() {
     test_parse(
@@ -256,6 +262,29 @@ fn test_repeat_indexed() {
     );
 }
 
+#[test]
+fn test_repeat_indexed_then_new_string() {
+    test_parse(
+        "|¨aa¨þ¨bb¨ß1÷",
+        vec![
+            "aa".to_string(),
+            "aa".to_string(),
+            "bb".to_string(),
+            "bb".to_string(),
+        ],
+    );
+    test_parse(
+        "|{¨key¨¨aa¨}þ{ß0¨bb¨}{ß0ß2}÷",
+        vec![
+            indexmap! { "key".to_string() => "aa".to_string() },
+            indexmap! { "key".to_string() => "aa".to_string() },
+            indexmap! { "key".to_string() => "bb".to_string() },
+            indexmap! { "key".to_string() => "bb".to_string() },
+        ],
+    );
+}
+
+#[cfg(feature = "preserve_order")]
 #[test]
 fn test_tuple() {
     #[derive(Deserialize, PartialEq, Debug)]
@@ -4,6 +4,12 @@ use serde_zipson::value::{Number, Value};
 
 #[test]
 fn test_one_of_each() {
+    // full precision floats are kept as text with arbitrary_precision
+    #[cfg(not(feature = "arbitrary_precision"))]
+    let full_precision = Number::Float(-0.552345411);
+    #[cfg(feature = "arbitrary_precision")]
+    let full_precision = Number::Raw("-0.552345411".into());
+
     test_parse("|§»«É¤A¢pc6w¢-2AH5Yxa£0.-1n£-0,552345411´´´x´¨aoasdfjalisruhgalsiuhfdlsajdlifuashrlifuhsaildjfsalkhglasurflasjdfklsandfasurliausnlc¨øSyKTET5±1739m÷", Value::Array(vec![
         Value::Null,
         Value::Bool(true),
@@ -13,7 +19,7 @@ fn test_one_of_each() {
         Value::Number(Number::Int(12301230)),
         Value::Number(Number::Int(-123014323230)),
         Value::Number(Number::Float(-0.111)),
-        Value::Number(Number::Float(-0.552345411)),
+        Value::Number(full_precision),
         Value::String("".to_string()),
         Value::String("x".to_string()),
         Value::String("aoasdfjalisruhgalsiuhfdlsajdlifuashrlifuhsaildjfsalkhglasurflasjdfklsandfasurliausnlc".to_string()),
@@ -84,3 +84,138 @@ fn test_nested() {
         },
     );
 }
+
+#[test]
+fn test_unit_like_short() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    enum UnitLike {
+        A,
+        B(i64),
+    }
+
+    test_parse("´A´", UnitLike::A);
+    test_parse("|´A´{´B´Ì}÷", vec![UnitLike::A, UnitLike::B(3)]);
+    test_parse("|¨A¨ß0÷", vec![UnitLike::A, UnitLike::A]);
+}
+
+#[test]
+fn test_internally_tagged() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    #[serde(tag = "type")]
+    enum Internal {
+        Unit,
+        Struct { id: i64, name: String },
+        Newtype(Inner),
+    }
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Inner {
+        x: i64,
+        y: f64,
+    }
+
+    test_parse("{¨type¨¨Unit¨}", Internal::Unit);
+    test_parse(
+        "{¨type¨¨Struct¨¨id¨¢EMnFO¨name¨´x´}",
+        Internal::Struct {
+            id: 212301230,
+            name: "x".into(),
+        },
+    );
+    test_parse(
+        "{¨type¨¨Newtype¨´x´Ê´y´£1.84}",
+        Internal::Newtype(Inner { x: 1, y: 1.5 }),
+    );
+    test_parse(
+        "|{¨type¨¨Unit¨}{ß0¨Struct¨¨id¨Î¨name¨ß4}÷",
+        vec![
+            Internal::Unit,
+            Internal::Struct {
+                id: 5,
+                name: "name".into(),
+            },
+        ],
+    );
+}
+
+#[test]
+fn test_adjacently_tagged() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    #[serde(tag = "t", content = "c")]
+    enum Adjacent {
+        Unit,
+        Newtype(i64),
+        Tuple(i64, String),
+        Struct { id: i64 },
+    }
+
+    test_parse("{´t´¨Unit¨}", Adjacent::Unit);
+    test_parse("{´t´¨Newtype¨´c´Î}", Adjacent::Newtype(5));
+    test_parse("{´t´¨Tuple¨´c´|Î´a´÷}", Adjacent::Tuple(5, "a".into()));
+    test_parse("{´t´¨Struct¨´c´{¨id¨Î}}", Adjacent::Struct { id: 5 });
+    test_parse(
+        "|{´t´¨Newtype¨´c´Î}{´t´¨Unit¨}÷",
+        vec![Adjacent::Newtype(5), Adjacent::Unit],
+    );
+}
+
+#[test]
+fn test_untagged() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    #[serde(untagged)]
+    enum Untagged {
+        Int(i64),
+        Str(String),
+        Struct { id: i64, name: String },
+        Tuple(bool, bool),
+    }
+
+    test_parse("Î", Untagged::Int(5));
+    test_parse("¢84", Untagged::Int(500));
+    test_parse("¨str¨", Untagged::Str("str".into()));
+    test_parse(
+        "{¨id¨Î¨name¨´n´}",
+        Untagged::Struct {
+            id: 5,
+            name: "n".into(),
+        },
+    );
+    test_parse("|»«÷", Untagged::Tuple(true, false));
+    test_parse("|Î´s´÷", vec![Untagged::Int(5), Untagged::Str("s".into())]);
+}
+
+#[test]
+fn test_compact_variants() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    enum Compact {
+        Unit,
+        Newtype(i64),
+        Tuple(i64, String),
+        Struct { id: i64 },
+    }
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    #[serde(tag = "t", content = "c")]
+    enum Adjacent {
+        Unit,
+        Newtype(i64),
+    }
+
+    test_parse("É", Compact::Unit);
+    test_parse("|ÊÊ÷", Compact::Newtype(1));
+    test_parse("|Ë|Ê´x´÷÷", Compact::Tuple(1, "x".into()));
+    test_parse("|Ì{¨id¨Ê}÷", Compact::Struct { id: 1 });
+    test_parse(
+        "|É|ÊÊ÷þ|Ë|Ê´x´÷÷|Ì{¨id¨Ê}÷÷",
+        vec![
+            Compact::Unit,
+            Compact::Newtype(1),
+            Compact::Newtype(1),
+            Compact::Tuple(1, "x".into()),
+            Compact::Struct { id: 1 },
+        ],
+    );
+    test_parse(
+        "|{´t´Ê´c´Ì}{´t´É}÷",
+        vec![Adjacent::Newtype(3), Adjacent::Unit],
+    );
+}
@@ -1,4 +1,5 @@
 use serde::Deserialize;
+use serde_zipson::de::DeserializerOptions;
 use std::fmt::Debug;
 
 pub mod array;
@@ -12,3 +13,12 @@ fn test_parse<'de, T: Deserialize<'de> + PartialEq + Debug>(str: &'de str, expec
     let res = serde_zipson::de::from_str::<T>(str);
     assert_eq!(res.unwrap(), expected);
 }
+
+fn test_parse_with_options<'de, T: Deserialize<'de> + PartialEq + Debug>(
+    str: &'de str,
+    options: DeserializerOptions,
+    expected: T,
+) {
+    let res = serde_zipson::de::from_str_with_options::<T>(str, options);
+    assert_eq!(res.unwrap(), expected);
+}
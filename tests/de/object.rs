@@ -1,14 +1,22 @@
 use crate::de::test_parse;
+#[cfg(feature = "preserve_order")]
+use crate::de::test_parse_with_options;
 
 use indexmap::{indexmap, IndexMap};
-use serde_zipson;
+#[cfg(feature = "preserve_order")]
+use serde_zipson::de::{DeserializerOptions, DuplicateKeys};
+#[cfg(feature = "preserve_order")]
+use serde_zipson::error::Error;
+#[cfg(feature = "preserve_order")]
 use serde_zipson::value::{Number, Value};
 
+#[cfg(feature = "preserve_order")]
 #[test]
 fn test_empty() {
     test_parse("{}", Value::Object(IndexMap::new()));
 }
 
+#[cfg(feature = "preserve_order")]
 #[test]
 fn test_homogenous() {
     test_parse(
@@ -21,6 +29,7 @@ fn test_homogenous() {
     );
 }
 
+#[cfg(feature = "preserve_order")]
 #[test]
 fn test_mixed() {
     test_parse(
@@ -36,6 +45,7 @@ fn test_mixed() {
     );
 }
 
+#[cfg(feature = "preserve_order")]
 #[test]
 fn test_nested() {
     test_parse("{´x´Ê´y´¢EMnFO´z´¨asdfioj{{}}¨´i´´´¨longkey¨»¨nope¨§¨float¨£TQZ.6y¨nest¨{´x´Ê´y´º0ß3£0.52´z´ß0´i´´´ß1»ß2§¨yep¨{´5´|§÷¨string¨¨\"\"asoidj{}sidofj¨}}¨array_nest¨|{´x´Ê´y´º0´z´ß0´i´´´ß1»ß2§}÷}", Value::Object(indexmap! {
@@ -71,3 +81,79 @@ fn test_nested() {
         ])
     }));
 }
+
+#[test]
+fn test_non_string_keys() {
+    #[derive(serde::Deserialize, PartialEq, Eq, Hash, Debug)]
+    enum Color {
+        Red,
+        Green,
+    }
+
+    test_parse(
+        "{´1´´a´¨200¨´b´}",
+        indexmap! { 1u32 => "a".to_string(), 200 => "b".to_string() },
+    );
+    test_parse("{¨-5¨Ê¨1234567¨Ë}", indexmap! { -5i64 => 1, 1234567 => 2 });
+    test_parse("{¨true¨Ê¨false¨Ë}", indexmap! { true => 1, false => 2 });
+    test_parse("{´c´Ê}", indexmap! { 'c' => 1 });
+    test_parse(
+        "{¨Red¨Ê¨Green¨Ë}",
+        indexmap! { Color::Red => 1, Color::Green => 2 },
+    );
+    test_parse(
+        "|{´1´Ê´2´Ë}{´1´Ê}÷",
+        vec![indexmap! { 1u32 => 1, 2 => 2 }, indexmap! { 1u32 => 1 }],
+    );
+    assert!(serde_zipson::de::from_str::<IndexMap<i64, i64>>("{´x´Ê}").is_err());
+}
+
+#[cfg(feature = "preserve_order")]
+#[test]
+fn test_duplicate_keys() {
+    #[derive(serde::Deserialize, PartialEq, Debug)]
+    struct Object {
+        x: i64,
+    }
+
+    let first_wins = DeserializerOptions {
+        duplicate_keys: DuplicateKeys::FirstWins,
+        ..DeserializerOptions::default()
+    };
+    let error = DeserializerOptions {
+        duplicate_keys: DuplicateKeys::Error,
+        ..DeserializerOptions::default()
+    };
+
+    test_parse(
+        "{´a´Ê´a´Ë}",
+        Value::Object(indexmap! { "a".into() => Value::Number(Number::Int(2)) }),
+    );
+    test_parse_with_options(
+        "{´a´Ê´a´Ë´b´Ì}",
+        first_wins.clone(),
+        Value::Object(indexmap! {
+            "a".into() => Value::Number(Number::Int(1)),
+            "b".into() => Value::Number(Number::Int(3)),
+        }),
+    );
+    test_parse_with_options("{´x´Ê´x´{´y´Ë}}", first_wins.clone(), Object { x: 1 });
+    test_parse_with_options(
+        "|{¨key¨Êß0Ë}{ß0Ì}÷",
+        first_wins,
+        vec![
+            Value::Object(indexmap! { "key".into() => Value::Number(Number::Int(1)) }),
+            Value::Object(indexmap! { "key".into() => Value::Number(Number::Int(3)) }),
+        ],
+    );
+
+    assert!(serde_zipson::de::from_str::<Object>("{´x´Ê´x´Ë}").is_err());
+    assert!(matches!(
+        serde_zipson::de::from_str_with_options::<Value>("{´a´Ê´a´Ë}", error.clone()),
+        Err(Error::DuplicateKey { key, position: 8 }) if key == "a"
+    ));
+    assert!(matches!(
+        serde_zipson::de::from_str_with_options::<Value>("{¨key¨Êß0Ë}", error),
+        Err(Error::DuplicateKey { key, position: 10 }) if key == "key"
+    ));
+}
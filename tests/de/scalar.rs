@@ -1,9 +1,14 @@
-use crate::de::test_parse;
+use crate::de::{test_parse, test_parse_with_options};
+use chrono::{DateTime, Utc};
+use serde_zipson::de::DeserializerOptions;
+use serde_zipson::error::Error;
 
 use serde_zipson::value::{
-    Number::{Float, Int},
+    Number::{Float, Int, UInt},
     Value,
 };
+#[cfg(feature = "std")]
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 #[test]
 fn test_null() {
@@ -53,6 +58,20 @@ fn test_big_integer() {
     test_parse("¢-2AH5Yxa", Value::Number(Int(-123014323230)));
 }
 
+#[test]
+fn test_u64_integer() {
+    test_parse("¢LygHa16AHYF", u64::MAX);
+    test_parse("¢LygHa16AHYF", Value::Number(UInt(u64::MAX)));
+    test_parse("¢-AzL8n0Y58m8", i64::MIN);
+    test_parse(
+        "|¢LygHa16AHYF¢LygHa16AHYEº0÷",
+        vec![u64::MAX, u64::MAX - 1, u64::MAX],
+    );
+    assert!(serde_zipson::de::from_str::<i64>("¢LygHa16AHYF").is_err());
+    assert!(serde_zipson::de::from_str::<u64>("¢LygHa16AHYG").is_err());
+    assert!(serde_zipson::de::from_str::<Value>("¢zzzzzzzzzzzzzzzzzzzzzzzzz").is_err());
+}
+
 #[test]
 fn test_unreferenced_integer() {
     test_parse(
@@ -92,6 +111,161 @@ fn test_float_small() {
     test_parse("£-F.-8x", Value::Number(Float(-15.555)));
 }
 
+#[test]
+fn test_float_negative() {
+    test_parse("£-1.-84", Value::Number(Float(-1.5)));
+    test_parse("£0.-84", Value::Number(Float(-0.5)));
+    test_parse("£-1z.-7M", Value::Number(Float(-123.456)));
+    test_parse("£0.-2", Value::Number(Float(-0.002)));
+    test_parse("£0.-G8", Value::Number(Float(-1.)));
+}
+
+#[test]
+fn test_float_from_integer() {
+    test_parse("Ê", 1f64);
+    test_parse("¢1z", 123f32);
+    test_parse("|Ê£1.84÷", vec![1f64, 1.5]);
+}
+
+#[test]
+fn test_numeric_coercion() {
+    test_parse("£2.0", 2u32);
+    test_parse("£-2,0", -2i8);
+    test_parse("|£2.0Ê÷", vec![2u32, 1]);
+    test_parse("|£2.0Ê÷", vec![2f64, 1.]);
+    assert!(serde_zipson::de::from_str::<u32>("£2.84").is_err());
+    assert!(serde_zipson::de::from_str::<u8>("£G4.0").is_err());
+    assert!(serde_zipson::de::from_str::<u32>("£-2.0").is_err());
+    assert!(serde_zipson::de::from_str::<i64>("£1,0e+300").is_err());
+    assert!(serde_zipson::de::from_str::<Vec<u32>>("|£2.84÷").is_err());
+
+    let strict = DeserializerOptions {
+        strict_numbers: true,
+        ..DeserializerOptions::default()
+    };
+    assert!(serde_zipson::de::from_str_with_options::<u32>("£2.0", strict.clone()).is_err());
+    assert!(serde_zipson::de::from_str_with_options::<f64>("Ê", strict.clone()).is_err());
+    test_parse_with_options("Ê", strict.clone(), 1u32);
+    test_parse_with_options("|ÊË÷", strict.clone(), vec![1u32, 2]);
+    test_parse_with_options("|£1.84þ÷", strict.clone(), vec![1.5f64, 1.5]);
+    assert!(serde_zipson::de::from_str_with_options::<Vec<f64>>("|ÊË÷", strict.clone()).is_err());
+    assert!(
+        serde_zipson::de::from_str_with_options::<Vec<i64>>("|£2,0£3,0÷", strict.clone()).is_err()
+    );
+    assert!(
+        serde_zipson::de::from_str_with_options::<Vec<i64>>("|Ê£2,0þ÷", strict.clone()).is_err()
+    );
+
+    #[derive(serde::Deserialize, PartialEq, Debug)]
+    struct F {
+        a: f64,
+        b: f64,
+    }
+
+    test_parse("|ÊË÷", F { a: 1., b: 2. });
+    test_parse_with_options("|£1.84£2.84÷", strict.clone(), F { a: 1.5, b: 2.5 });
+    assert!(serde_zipson::de::from_str_with_options::<F>("|ÊË÷", strict.clone()).is_err());
+    assert!(serde_zipson::de::from_str_with_options::<F>("{´a´Ê´b´Ë}", strict.clone()).is_err());
+
+    // dates are only read as millis through the date module
+    #[cfg(feature = "std")]
+    #[derive(serde::Deserialize, PartialEq, Debug)]
+    struct D {
+        #[serde(with = "serde_zipson::date")]
+        at: SystemTime,
+    }
+
+    test_parse("øSyKTET5", 1645677060123i64);
+    assert!(serde_zipson::de::from_str_with_options::<i64>("øSyKTET5", strict.clone()).is_err());
+    assert!(
+        serde_zipson::de::from_str_with_options::<Vec<i64>>("|øSyKTET5×0÷", strict.clone())
+            .is_err()
+    );
+    #[cfg(feature = "std")]
+    test_parse_with_options(
+        "{´at´øSyKTET5}",
+        strict,
+        D {
+            at: UNIX_EPOCH + Duration::from_millis(1645677060123),
+        },
+    );
+}
+
+// read as `Number::Raw` with arbitrary_precision
+#[cfg(not(feature = "arbitrary_precision"))]
+#[test]
+fn test_float_exponent() {
+    test_parse("£1,0e+300", Value::Number(Float(1e300)));
+    test_parse("£-1,7976931348623157e+308", Value::Number(Float(f64::MIN)));
+    test_parse("£-1,5e-10", Value::Number(Float(-1.5e-10)));
+    test_parse(
+        "|£5,0e-324Ê÷",
+        Value::Array(vec![Value::Number(Float(5e-324)), Value::Number(Int(1))]),
+    );
+}
+
+#[cfg(feature = "arbitrary_precision")]
+#[test]
+fn test_raw_numbers() {
+    use serde_zipson::value::Number::Raw;
+
+    test_parse(
+        "£uXgbDVmIp,891234",
+        Value::Number(Raw("12345678901234567.891234".into())),
+    );
+    test_parse(
+        "|£C,50Ý0£1,5e-10£C.U÷",
+        Value::Array(vec![
+            Value::Number(Raw("12.50".into())),
+            Value::Number(Raw("12.50".into())),
+            Value::Number(Raw("1.5e-10".into())),
+            Value::Number(Float(12.03)),
+        ]),
+    );
+
+    test_parse(
+        "¢1f2SI9UJPXvb7vdJ1",
+        Value::Number(Raw("79228162514264337593543950335".into())),
+    );
+
+    // written back as they were read
+    for input in [
+        "£uXgbDVmIp,891234",
+        "{¨amount¨£C,50¨fee¨£-0,050¨total¨Ý0}",
+        "|£1,5e-10£1,0e+20Ý0¢1f2SI9UJPXvb7vdJ1º0÷",
+    ] {
+        let value = serde_zipson::de::from_str::<Value>(input).unwrap();
+        assert_eq!(
+            serde_zipson::ser::to_string(&value, false, false).unwrap(),
+            input
+        );
+    }
+
+    // typed targets read the text as a number
+    test_parse("|£C,50£1,5e-10÷", vec![12.5f64, 1.5e-10]);
+    let value = serde_zipson::de::from_str::<Value>("|£C,50Ê÷").unwrap();
+    assert_eq!(
+        <Vec<f64> as serde::Deserialize>::deserialize(value).unwrap(),
+        vec![12.5, 1.]
+    );
+}
+
+// read as `Number::Raw` with arbitrary_precision
+#[cfg(not(feature = "arbitrary_precision"))]
+#[test]
+fn test_wide_integers() {
+    // only decimals are written with more than 64 bits
+    test_parse(
+        "|¢1f2SI9UJPXvb7vdJ1¢-1f2SI9UJPXvb7vdJ1÷",
+        Value::Array(vec![
+            Value::Number(Float(79228162514264337593543950335.)),
+            Value::Number(Float(-79228162514264337593543950335.)),
+        ]),
+    );
+}
+
+// read as `Number::Raw` with arbitrary_precision
+#[cfg(not(feature = "arbitrary_precision"))]
 #[test]
 fn test_float_full_precision() {
     test_parse("£0,0", Value::Number(Float(0.)));
@@ -101,6 +275,8 @@ fn test_float_full_precision() {
     test_parse("£-0,552345411", Value::Number(Float(-0.552345411)));
 }
 
+// read as `Number::Raw` with arbitrary_precision
+#[cfg(not(feature = "arbitrary_precision"))]
 #[test]
 fn test_unreferenced_float() {
     test_parse(
@@ -290,3 +466,68 @@ fn test_ref_lp_date() {
         ]),
     );
 }
+
+#[test]
+fn test_date_targets() {
+    test_parse("øSyKTET5", "2022-02-24T04:31:00.123Z".to_string());
+    test_parse("øSyKTET5", 1645677060123i64);
+    test_parse("±1739m", 1645677000000i64);
+    test_parse(
+        "øSyKTET5",
+        "2022-02-24T04:31:00.123Z".parse::<DateTime<Utc>>().unwrap(),
+    );
+    test_parse(
+        "|øSyKTET5×0±1739m÷",
+        vec![1645677060123i64, 1645677060123, 1645677000000],
+    );
+    test_parse(
+        "|øSyKTET5þ÷",
+        vec![
+            "2022-02-24T04:31:00.123Z".parse::<DateTime<Utc>>().unwrap(),
+            "2022-02-24T04:31:00.123Z".parse::<DateTime<Utc>>().unwrap(),
+        ],
+    );
+}
+
+#[cfg(feature = "system_time")]
+#[test]
+fn test_date_system_time() {
+    #[cfg(feature = "std")]
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    test_parse(
+        "øSyKTET5",
+        UNIX_EPOCH + Duration::from_millis(1645677060123),
+    );
+    test_parse(
+        "|øSyKTET5¿1÷",
+        vec![
+            UNIX_EPOCH + Duration::from_millis(1645677060123),
+            UNIX_EPOCH + Duration::from_millis(1),
+        ],
+    );
+    assert!(serde_zipson::de::from_str::<SystemTime>("¿-1").is_err());
+}
+
+#[test]
+fn test_pre_1970_date() {
+    test_parse(
+        "|ø-1ø-cEv2OGF±-DF4K×1÷",
+        Value::Array(vec![
+            Value::String("1969-12-31T23:59:59.999Z".into()),
+            Value::String("1901-03-04T05:06:07.089Z".into()),
+            Value::String("1960-01-01T00:00:00.000Z".into()),
+            Value::String("1901-03-04T05:06:07.089Z".into()),
+        ]),
+    );
+}
+
+#[test]
+fn test_lp_date_overflow() {
+    assert!(matches!(
+        serde_zipson::de::from_str::<Value>("±zzzzzzzzzz"),
+        Err(Error::ExpectedLpDate)
+    ));
+    assert!(serde_zipson::de::from_str::<String>("±zzzzzzzzzz").is_err());
+    assert!(serde_zipson::de::from_str::<i64>("ÿ-zzzzzzzzzz").is_err());
+}
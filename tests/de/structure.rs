@@ -1,8 +1,12 @@
 use crate::de::test_parse;
+use chrono::{DateTime, Utc};
 use indexmap::{indexmap, IndexMap};
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use serde_zipson::ser::SerializerOptions;
 use serde_zipson::value::{Number, Value};
+#[cfg(all(feature = "chrono", feature = "std"))]
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 #[test]
 fn test_empty() {
@@ -48,6 +52,7 @@ fn test_mixed() {
     );
 }
 
+#[cfg(feature = "preserve_order")]
 #[test]
 fn test_nested() {
     #[derive(Deserialize, PartialEq, Debug)]
@@ -124,6 +129,7 @@ fn nest_newtype() {
     test_parse("¢1z", IntNewType(123));
 }
 
+#[cfg(feature = "preserve_order")]
 #[test]
 fn nest_newtype_nested() {
     #[derive(Deserialize, PartialEq, Debug)]
@@ -167,6 +173,7 @@ fn test_unit() {
     test_parse("§", UnitStruct(()));
 }
 
+#[cfg(feature = "preserve_order")]
 #[test]
 fn test_tuple() {
     #[derive(Deserialize, PartialEq, Debug)]
@@ -209,3 +216,502 @@ fn test_tuple() {
         ),
     );
 }
+
+#[test]
+fn test_flatten() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Envelope {
+        id: i64,
+        #[serde(flatten)]
+        meta: Meta,
+        #[serde(flatten)]
+        extra: Option<Extra>,
+    }
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Meta {
+        version: i64,
+        #[serde(flatten)]
+        inner: Inner,
+    }
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Inner {
+        source: String,
+        ratio: f64,
+    }
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Extra {
+        note: String,
+    }
+
+    test_parse(
+        "{¨id¨Ê¨version¨Ë¨source¨¨src¨¨ratio¨£1.84}",
+        Envelope {
+            id: 1,
+            meta: Meta {
+                version: 2,
+                inner: Inner {
+                    source: "src".into(),
+                    ratio: 1.5,
+                },
+            },
+            extra: None,
+        },
+    );
+    test_parse(
+        "{¨id¨Ê¨version¨Ë¨source¨¨src¨¨ratio¨£1.84¨note¨¨hi¨}",
+        Envelope {
+            id: 1,
+            meta: Meta {
+                version: 2,
+                inner: Inner {
+                    source: "src".into(),
+                    ratio: 1.5,
+                },
+            },
+            extra: Some(Extra { note: "hi".into() }),
+        },
+    );
+    test_parse(
+        "|{¨id¨¢1c¨version¨Ë¨source¨¨src¨¨ratio¨£2.0}{ß0Êß1Ëß2ß3ß4Ý0}÷",
+        vec![
+            Envelope {
+                id: 100,
+                meta: Meta {
+                    version: 2,
+                    inner: Inner {
+                        source: "src".into(),
+                        ratio: 2.0,
+                    },
+                },
+                extra: None,
+            },
+            Envelope {
+                id: 1,
+                meta: Meta {
+                    version: 2,
+                    inner: Inner {
+                        source: "src".into(),
+                        ratio: 2.0,
+                    },
+                },
+                extra: None,
+            },
+        ],
+    );
+}
+
+#[test]
+fn test_flatten_map() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Object {
+        id: i64,
+        #[serde(flatten)]
+        rest: IndexMap<String, Value>,
+    }
+
+    test_parse(
+        "{¨id¨Ê´a´Ê´b´¢4q}",
+        Object {
+            id: 1,
+            rest: indexmap! {
+                "a".into() => Value::Number(Number::Int(1)),
+                "b".into() => Value::Number(Number::Int(300)),
+            },
+        },
+    );
+}
+
+#[test]
+fn test_compact() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Point {
+        x: i64,
+        y: i64,
+        label: String,
+        nested: Nested,
+    }
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Nested {
+        a: f64,
+        b: Option<i64>,
+    }
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Same {
+        a: i64,
+        b: i64,
+        c: i64,
+        d: i64,
+        e: i64,
+        f: i64,
+    }
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    enum StructLike {
+        Value { id: i64, name: String },
+    }
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Holder {
+        items: Vec<StructLike>,
+    }
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Defaulted {
+        x: i64,
+        #[serde(default)]
+        y: i64,
+    }
+
+    test_parse(
+        "|ÊË¨point¨|£1.84§÷÷",
+        Point {
+            x: 1,
+            y: 2,
+            label: "point".into(),
+            nested: Nested { a: 1.5, b: None },
+        },
+    );
+    test_parse(
+        "||ÊË¨point¨|£1.84§÷÷|ÊËß0|Ý0Ì÷÷÷",
+        vec![
+            Point {
+                x: 1,
+                y: 2,
+                label: "point".into(),
+                nested: Nested { a: 1.5, b: None },
+            },
+            Point {
+                x: 1,
+                y: 2,
+                label: "point".into(),
+                nested: Nested { a: 1.5, b: Some(3) },
+            },
+        ],
+    );
+    test_parse(
+        "|Êþþþ^2÷",
+        Same {
+            a: 1,
+            b: 1,
+            c: 1,
+            d: 1,
+            e: 1,
+            f: 1,
+        },
+    );
+    test_parse(
+        "{¨Value¨|Ê´n´÷}",
+        StructLike::Value {
+            id: 1,
+            name: "n".into(),
+        },
+    );
+    test_parse(
+        "|{¨Value¨|Ê´n´÷}{ß0|Ë´m´÷}÷",
+        vec![
+            StructLike::Value {
+                id: 1,
+                name: "n".into(),
+            },
+            StructLike::Value {
+                id: 2,
+                name: "m".into(),
+            },
+        ],
+    );
+    test_parse(
+        "|||É|Ê´n´÷÷|É|Ë´m´÷÷÷÷",
+        Holder {
+            items: vec![
+                StructLike::Value {
+                    id: 1,
+                    name: "n".into(),
+                },
+                StructLike::Value {
+                    id: 2,
+                    name: "m".into(),
+                },
+            ],
+        },
+    );
+
+    // trailing fields with defaults can be left out
+    test_parse("|Ê÷", Defaulted { x: 1, y: 0 });
+    test_parse(
+        "||Ê÷|ÊË÷÷",
+        vec![Defaulted { x: 1, y: 0 }, Defaulted { x: 1, y: 2 }],
+    );
+}
+
+#[test]
+fn test_compact_adjacently_tagged() {
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    #[serde(tag = "t", content = "c")]
+    enum Adjacent {
+        Value { id: i64, name: String },
+        Other(i64),
+    }
+
+    let items = vec![
+        Adjacent::Value {
+            id: 1,
+            name: "n".into(),
+        },
+        Adjacent::Other(2),
+    ];
+    let res = serde_zipson::ser::to_string_with_options(
+        &items,
+        SerializerOptions {
+            compact_structs: true,
+            ..SerializerOptions::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(res, "||¨Value¨{¨id¨Ê¨name¨´n´}÷|¨Other¨Ë÷÷");
+    test_parse(&res, items);
+}
+
+#[test]
+fn test_dates() {
+    #[derive(Deserialize, PartialEq, Debug, Clone)]
+    struct Event {
+        raw: String,
+        at: DateTime<Utc>,
+        millis: i64,
+        seen: Option<DateTime<Utc>>,
+    }
+
+    test_parse(
+        "{´raw´øSyKTET5´at´×0¨millis¨×0¨seen¨±1739m}",
+        Event {
+            raw: "2022-02-24T04:31:00.123Z".into(),
+            at: "2022-02-24T04:31:00.123Z".parse().unwrap(),
+            millis: 1645677060123,
+            seen: Some("2022-02-24T04:30:00Z".parse().unwrap()),
+        },
+    );
+    test_parse(
+        "|{´raw´øSyKTET5´at´×0¨millis¨×0¨seen¨§}þ÷",
+        vec![
+            Event {
+                raw: "2022-02-24T04:31:00.123Z".into(),
+                at: "2022-02-24T04:31:00.123Z".parse().unwrap(),
+                millis: 1645677060123,
+                seen: None,
+            };
+            2
+        ],
+    );
+    test_parse("|øSyKTET5×0÷", vec![1645677060123i64; 2]);
+
+    // only date tokens are read as millis, whatever the nesting
+    assert!(serde_zipson::de::from_str::<i64>("¨2022-02-24T04:31:00.123Z¨").is_err());
+    assert!(serde_zipson::de::from_str::<Vec<i64>>("|¨2022-02-24T04:31:00.123Z¨÷").is_err());
+}
+
+#[cfg(all(feature = "chrono", feature = "std"))]
+#[test]
+fn test_date_module() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Event {
+        #[serde(with = "serde_zipson::date")]
+        at: DateTime<Utc>,
+        #[serde(with = "serde_zipson::date")]
+        system: SystemTime,
+    }
+
+    test_parse(
+        "{´at´øSyKTET5¨system¨±1739m}",
+        Event {
+            at: "2022-02-24T04:31:00.123Z".parse().unwrap(),
+            system: UNIX_EPOCH + Duration::from_secs(1645677000),
+        },
+    );
+    test_parse(
+        "{´at´¿-1¨system¨¿-1}",
+        Event {
+            at: "1969-12-31T23:59:59.999Z".parse().unwrap(),
+            system: UNIX_EPOCH - Duration::from_millis(1),
+        },
+    );
+}
+
+#[cfg(all(feature = "chrono", feature = "std"))]
+#[test]
+fn test_date_module_buffered() {
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Inner {
+        #[serde(with = "serde_zipson::date")]
+        at: DateTime<Utc>,
+    }
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Flattened {
+        id: i64,
+        #[serde(flatten)]
+        inner: Inner,
+    }
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    #[serde(tag = "type")]
+    enum Tagged {
+        Event {
+            #[serde(with = "serde_zipson::date")]
+            at: SystemTime,
+        },
+    }
+
+    let flattened = Flattened {
+        id: 1,
+        inner: Inner {
+            at: "2022-02-24T04:31:00.123Z".parse().unwrap(),
+        },
+    };
+    let res = serde_zipson::ser::to_string(&flattened, false, false).unwrap();
+    assert_eq!(res, "{¨id¨Ê¨at¨øSyKTET5}");
+    test_parse(&res, flattened);
+
+    let tagged = Tagged::Event {
+        at: UNIX_EPOCH - Duration::from_millis(1),
+    };
+    let res = serde_zipson::ser::to_string(&tagged, false, false).unwrap();
+    assert_eq!(res, "{¨type¨¨Event¨¨at¨ø-1}");
+    test_parse(&res, tagged);
+}
+
+#[test]
+fn test_float_precision_fields() {
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Reading {
+        #[serde(with = "serde_zipson::float::reduced")]
+        temperature: f64,
+        #[serde(with = "serde_zipson::float::full")]
+        price: Option<f64>,
+    }
+
+    test_parse(
+        "{¨temperature¨£L.1z¨price¨£9,9999}",
+        Reading {
+            temperature: 21.123,
+            price: Some(9.9999),
+        },
+    );
+}
+
+#[cfg(feature = "decimal")]
+#[test]
+fn test_decimal_fields() {
+    use rust_decimal::Decimal;
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Payment {
+        #[serde(with = "serde_zipson::decimal")]
+        amount: Decimal,
+        #[serde(with = "serde_zipson::decimal")]
+        fee: Decimal,
+        #[serde(with = "serde_zipson::decimal")]
+        total: Decimal,
+        #[serde(with = "serde_zipson::decimal")]
+        refund: Decimal,
+    }
+
+    test_parse(
+        "{¨amount¨£C,50¨fee¨£0.-1M¨total¨¢1f2SI9UJPXvb7vdJ1¨refund¨Ý0}",
+        Payment {
+            amount: "12.50".parse().unwrap(),
+            fee: "-0.084".parse().unwrap(),
+            total: "79228162514264337593543950335".parse().unwrap(),
+            refund: "12.50".parse().unwrap(),
+        },
+    );
+    test_parse(
+        "|{¨amount¨£0,1¨fee¨Ê¨total¨£1,5e-10¨refund¨¥0.5}÷",
+        vec![Payment {
+            amount: "0.1".parse().unwrap(),
+            fee: "1".parse().unwrap(),
+            total: "0.00000000015".parse().unwrap(),
+            refund: "0.005".parse().unwrap(),
+        }],
+    );
+
+    // integral decimals keep their scale
+    let payment: Payment =
+        serde_zipson::de::from_str("{¨amount¨£C,0¨fee¨¤C¨total¨¢fFgnDxSe9¨refund¨À}").unwrap();
+    assert_eq!(payment.amount.to_string(), "12.0");
+    assert_eq!(payment.fee.to_string(), "12");
+    assert_eq!(payment.total.to_string(), "9007199254740993");
+    assert_eq!(payment.refund.to_string(), "-9");
+
+    #[derive(Deserialize, PartialEq, Debug, Clone)]
+    struct Line {
+        #[serde(with = "serde_zipson::decimal")]
+        price: Decimal,
+        quantity: i64,
+    }
+
+    test_parse(
+        "|£uXgbDVmIp,891234Ê÷",
+        Line {
+            price: "12345678901234567.891234".parse().unwrap(),
+            quantity: 1,
+        },
+    );
+    test_parse(
+        "||£uXgbDVmIp,891234Ê÷þ÷",
+        vec![
+            Line {
+                price: "12345678901234567.891234".parse().unwrap(),
+                quantity: 1,
+            };
+            2
+        ],
+    );
+
+    // decimals buffered as an f64 can't be read back exactly
+    #[derive(Deserialize, PartialEq, Debug)]
+    #[serde(tag = "type")]
+    enum Tagged {
+        Line {
+            #[serde(with = "serde_zipson::decimal")]
+            price: Decimal,
+        },
+    }
+
+    assert!(serde_zipson::de::from_str::<Tagged>("{¨type¨¨Line¨¨price¨£C,5}").is_err());
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Flattened {
+        id: i64,
+        #[serde(flatten)]
+        line: Line,
+    }
+
+    assert!(serde_zipson::de::from_str::<Flattened>("{¨id¨Ê¨price¨£C,5¨quantity¨Ê}").is_err());
+}
+
+#[cfg(feature = "time")]
+#[test]
+fn test_time_date_module() {
+    use time::OffsetDateTime;
+
+    #[derive(Deserialize, PartialEq, Debug)]
+    struct Event {
+        #[serde(with = "serde_zipson::date")]
+        at: OffsetDateTime,
+        #[serde(with = "serde_zipson::date")]
+        before: OffsetDateTime,
+    }
+
+    test_parse(
+        "{´at´øSyKTET5¨before¨¿-1}",
+        Event {
+            at: OffsetDateTime::from_unix_timestamp_nanos(1_645_677_060_123_000_000).unwrap(),
+            before: OffsetDateTime::from_unix_timestamp_nanos(-1_000_000).unwrap(),
+        },
+    );
+}
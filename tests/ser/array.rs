@@ -1,6 +1,8 @@
 use crate::ser::{test_stringify, test_stringify_detect_dates, test_stringify_full_precision};
 
+#[cfg(feature = "preserve_order")]
 use indexmap::{indexmap, IndexMap};
+#[cfg(feature = "preserve_order")]
 use serde::Serialize;
 use serde_zipson::value::{Number, Value};
 
@@ -200,6 +202,7 @@ fn test_repeat_array() {
     );
 }
 
+#[cfg(feature = "preserve_order")]
 #[test]
 fn test_repeat_object() {
     test_stringify(
@@ -272,6 +275,7 @@ fn test_repeat_indexed() {
     );
 }
 
+#[cfg(feature = "preserve_order")]
 #[test]
 fn test_tuple() {
     #[derive(Serialize)]
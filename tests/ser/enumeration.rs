@@ -1,6 +1,7 @@
-use crate::ser::test_stringify;
+use crate::ser::{test_stringify, test_stringify_with_options};
 
 use serde::Serialize;
+use serde_zipson::ser::SerializerOptions;
 
 #[test]
 fn test_unit_like() {
@@ -83,3 +84,144 @@ fn test_nested() {
         "{¨Value¨{¨string¨ß1¨int¨¢EMnFO¨float¨£0.52¨nested¨{ß0|ß1º0Ý0÷}}}",
     );
 }
+
+#[test]
+fn test_unit_like_short() {
+    #[derive(Serialize)]
+    enum UnitLike {
+        A,
+        B(i64),
+    }
+
+    test_stringify(UnitLike::A, "´A´");
+    test_stringify(vec![UnitLike::A, UnitLike::B(3)], "|´A´{´B´Ì}÷");
+}
+
+#[test]
+fn test_internally_tagged() {
+    #[derive(Serialize)]
+    #[serde(tag = "type")]
+    enum Internal {
+        Unit,
+        Struct { id: i64, name: String },
+        Newtype(Inner),
+    }
+    #[derive(Serialize)]
+    struct Inner {
+        x: i64,
+        y: f64,
+    }
+
+    test_stringify(Internal::Unit, "{¨type¨¨Unit¨}");
+    test_stringify(
+        Internal::Struct {
+            id: 212301230,
+            name: "x".into(),
+        },
+        "{¨type¨¨Struct¨¨id¨¢EMnFO¨name¨´x´}",
+    );
+    test_stringify(
+        Internal::Newtype(Inner { x: 1, y: 1.5 }),
+        "{¨type¨¨Newtype¨´x´Ê´y´£1.84}",
+    );
+    test_stringify(
+        vec![
+            Internal::Unit,
+            Internal::Struct {
+                id: 5,
+                name: "name".into(),
+            },
+        ],
+        "|{¨type¨¨Unit¨}{ß0¨Struct¨¨id¨Î¨name¨ß4}÷",
+    );
+}
+
+#[test]
+fn test_adjacently_tagged() {
+    #[derive(Serialize)]
+    #[serde(tag = "t", content = "c")]
+    enum Adjacent {
+        Unit,
+        Newtype(i64),
+        Tuple(i64, String),
+        Struct { id: i64 },
+    }
+
+    test_stringify(Adjacent::Unit, "{´t´¨Unit¨}");
+    test_stringify(Adjacent::Newtype(5), "{´t´¨Newtype¨´c´Î}");
+    test_stringify(Adjacent::Tuple(5, "a".into()), "{´t´¨Tuple¨´c´|Î´a´÷}");
+    test_stringify(Adjacent::Struct { id: 5 }, "{´t´¨Struct¨´c´{¨id¨Î}}");
+    test_stringify(
+        vec![Adjacent::Newtype(5), Adjacent::Unit],
+        "|{´t´¨Newtype¨´c´Î}{´t´¨Unit¨}÷",
+    );
+}
+
+#[test]
+fn test_untagged() {
+    #[derive(Serialize)]
+    #[serde(untagged)]
+    enum Untagged {
+        Int(i64),
+        Str(String),
+        Struct { id: i64, name: String },
+        Tuple(bool, bool),
+    }
+
+    test_stringify(Untagged::Int(5), "Î");
+    test_stringify(Untagged::Int(500), "¢84");
+    test_stringify(Untagged::Str("str".into()), "¨str¨");
+    test_stringify(
+        Untagged::Struct {
+            id: 5,
+            name: "n".into(),
+        },
+        "{¨id¨Î¨name¨´n´}",
+    );
+    test_stringify(Untagged::Tuple(true, false), "|»«÷");
+    test_stringify(vec![Untagged::Int(5), Untagged::Str("s".into())], "|Î´s´÷");
+}
+
+#[test]
+fn test_compact_variants() {
+    #[derive(Serialize)]
+    enum Compact {
+        Unit,
+        Newtype(i64),
+        Tuple(i64, String),
+        Struct { id: i64 },
+    }
+
+    #[derive(Serialize)]
+    #[serde(tag = "t", content = "c")]
+    enum Adjacent {
+        Unit,
+        Newtype(i64),
+    }
+
+    let options = SerializerOptions {
+        compact_variants: true,
+        ..SerializerOptions::default()
+    };
+
+    test_stringify_with_options(Compact::Unit, options.clone(), "É");
+    test_stringify_with_options(Compact::Newtype(1), options.clone(), "|ÊÊ÷");
+    test_stringify_with_options(Compact::Tuple(1, "x".into()), options.clone(), "|Ë|Ê´x´÷÷");
+    test_stringify_with_options(Compact::Struct { id: 1 }, options.clone(), "|Ì{¨id¨Ê}÷");
+    test_stringify_with_options(
+        vec![
+            Compact::Unit,
+            Compact::Newtype(1),
+            Compact::Newtype(1),
+            Compact::Tuple(1, "x".into()),
+            Compact::Struct { id: 1 },
+        ],
+        options.clone(),
+        "|É|ÊÊ÷þ|Ë|Ê´x´÷÷|Ì{¨id¨Ê}÷÷",
+    );
+    test_stringify_with_options(
+        vec![Adjacent::Newtype(3), Adjacent::Unit],
+        options,
+        "|{´t´Ê´c´Ì}{´t´É}÷",
+    );
+}
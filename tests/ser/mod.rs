@@ -1,4 +1,5 @@
 use serde::Serialize;
+use serde_zipson::ser::SerializerOptions;
 
 pub mod array;
 pub mod array_mixed;
@@ -21,3 +22,8 @@ fn test_stringify_detect_dates<T: Serialize>(value: T, expected: &str) {
     let res = serde_zipson::ser::to_string::<T>(&value, false, true);
     assert_eq!(res.unwrap(), expected);
 }
+
+fn test_stringify_with_options<T: Serialize>(value: T, options: SerializerOptions, expected: &str) {
+    let res = serde_zipson::ser::to_string_with_options::<T>(&value, options);
+    assert_eq!(res.unwrap(), expected);
+}
@@ -1,13 +1,24 @@
 use crate::ser::test_stringify;
+#[cfg(feature = "preserve_order")]
+use crate::ser::test_stringify_with_options;
 
-use indexmap::{indexmap, IndexMap};
+use indexmap::indexmap;
+#[cfg(feature = "preserve_order")]
+use indexmap::IndexMap;
+#[cfg(feature = "preserve_order")]
+use serde_zipson::ser::SerializerOptions;
+#[cfg(feature = "preserve_order")]
 use serde_zipson::value::{Number, Value};
+#[cfg(feature = "preserve_order")]
+use std::collections::{BTreeSet, HashMap, HashSet};
 
+#[cfg(feature = "preserve_order")]
 #[test]
 fn test_empty() {
     test_stringify(Value::Object(IndexMap::new()), "{}");
 }
 
+#[cfg(feature = "preserve_order")]
 #[test]
 fn test_homogenous() {
     test_stringify(
@@ -20,6 +31,7 @@ fn test_homogenous() {
     );
 }
 
+#[cfg(feature = "preserve_order")]
 #[test]
 fn test_mixed() {
     test_stringify(
@@ -35,6 +47,7 @@ fn test_mixed() {
     );
 }
 
+#[cfg(feature = "preserve_order")]
 #[test]
 fn test_nested() {
     test_stringify(Value::Object(indexmap! {
@@ -70,3 +83,109 @@ fn test_nested() {
         ])
     }), "{´x´Ê´y´¢EMnFO´z´¨asdfioj{{}}¨´i´´´¨longkey¨»¨nope¨§¨float¨£TQZ.6y¨nest¨{´x´Ê´y´º0ß3£0.52´z´ß0´i´´´ß1»ß2§¨yep¨{´5´|§÷¨string¨¨\"\"asoidj{}sidofj¨}}¨array_nest¨|{´x´Ê´y´º0´z´ß0´i´´´ß1»ß2§}÷}");
 }
+
+#[test]
+fn test_non_string_keys() {
+    #[derive(serde::Serialize, PartialEq, Eq, Hash)]
+    enum Color {
+        Red,
+        Green,
+    }
+
+    test_stringify(indexmap! { 1u32 => "a", 200 => "b" }, "{´1´´a´¨200¨´b´}");
+    test_stringify(indexmap! { -5i64 => 1, 1234567 => 2 }, "{¨-5¨Ê¨1234567¨Ë}");
+    test_stringify(indexmap! { true => 1, false => 2 }, "{¨true¨Ê¨false¨Ë}");
+    test_stringify(indexmap! { 'c' => 1 }, "{´c´Ê}");
+    test_stringify(
+        indexmap! { Color::Red => 1, Color::Green => 2 },
+        "{¨Red¨Ê¨Green¨Ë}",
+    );
+    test_stringify(
+        vec![indexmap! { 1u32 => 1, 2 => 2 }, indexmap! { 1u32 => 1 }],
+        "|{´1´Ê´2´Ë}{´1´Ê}÷",
+    );
+}
+
+#[test]
+fn test_float_keys() {
+    struct FloatKeys(Vec<f64>);
+
+    impl serde::Serialize for FloatKeys {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            use serde::ser::SerializeMap;
+
+            let mut map = serializer.serialize_map(Some(self.0.len()))?;
+            for key in &self.0 {
+                map.serialize_entry(key, &1)?;
+            }
+            map.end()
+        }
+    }
+
+    test_stringify(
+        FloatKeys(vec![f64::NAN, f64::INFINITY, -1.5, 2.0]),
+        "{¨NaN¨Ê¨Infinity¨Ê¨-1.5¨Ê´2´Ê}",
+    );
+    test_stringify(
+        FloatKeys(vec![1e21, 1e20, -1.5e-7, 0.000001, -0.]),
+        "{¨1e+21¨Ê¨100000000000000000000¨Ê¨-1.5e-7¨Ê¨0.000001¨Ê´0´Ê}",
+    );
+}
+
+#[cfg(feature = "preserve_order")]
+#[test]
+fn test_canonical() {
+    let options = SerializerOptions {
+        canonical: true,
+        ..SerializerOptions::default()
+    };
+
+    test_stringify_with_options(
+        indexmap! { "b" => 2, "a" => 1, "c" => 3 },
+        options.clone(),
+        "{´a´Ê´b´Ë´c´Ì}",
+    );
+    test_stringify_with_options(
+        Value::Object(indexmap! {
+            "zeta".into() => Value::String("last".into()),
+            "alpha".into() => Value::Object(indexmap! {
+                "2".into() => Value::String("last".into()),
+                "10".into() => Value::String("first".into()),
+            }),
+        }),
+        options.clone(),
+        "{¨alpha¨{¨10¨¨first¨´2´¨last¨}¨zeta¨ß3}",
+    );
+
+    for _ in 0..10 {
+        let map: HashMap<String, Vec<HashMap<u32, String>>> = (0..10)
+            .map(|i| {
+                (
+                    format!("key{}", i),
+                    vec![HashMap::from([
+                        (i, "value".to_string()),
+                        (100, "other".to_string()),
+                    ])],
+                )
+            })
+            .collect();
+
+        test_stringify_with_options(
+            map,
+            options.clone(),
+            "{¨key0¨|{´0´¨value¨¨100¨¨other¨}÷¨key1¨|{´1´ß1ß2ß3}÷¨key2¨|{ß2ß3´2´ß1}÷¨key3¨|{ß2ß3´3´ß1}÷¨key4¨|{ß2ß3´4´ß1}÷¨key5¨|{ß2ß3´5´ß1}÷¨key6¨|{ß2ß3´6´ß1}÷¨key7¨|{ß2ß3´7´ß1}÷¨key8¨|{ß2ß3´8´ß1}÷¨key9¨|{ß2ß3´9´ß1}÷}",
+        );
+    }
+
+    // sequences keep their order, so sets are only deterministic once sorted
+    test_stringify_with_options(vec!["b", "a"], options.clone(), "|´b´´a´÷");
+    for _ in 0..10 {
+        let set: HashSet<&str> = HashSet::from(["b", "a", "c", "d"]);
+
+        test_stringify_with_options(
+            set.into_iter().collect::<BTreeSet<_>>(),
+            options.clone(),
+            "|´a´´b´´c´´d´÷",
+        );
+    }
+}
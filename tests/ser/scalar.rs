@@ -1,8 +1,16 @@
-use crate::ser::{test_stringify, test_stringify_detect_dates, test_stringify_full_precision};
+use crate::ser::{
+    test_stringify, test_stringify_detect_dates, test_stringify_full_precision,
+    test_stringify_with_options,
+};
 
-use serde_zipson::ser::to_string;
+use chrono::{DateTime, SecondsFormat};
+use serde_zipson::error::Error;
+use serde_zipson::ser::{
+    to_string, to_string_with_options, FloatPrecision, NonFiniteFloats, SerializerOptions,
+    UnsafeIntegers,
+};
 use serde_zipson::value::{
-    Number::{Float, Int},
+    Number::{Float, Int, UInt},
     Value,
 };
 
@@ -54,6 +62,49 @@ fn test_big_integer() {
     test_stringify(Value::Number(Int(-123014323230)), "¢-2AH5Yxa");
 }
 
+#[test]
+fn test_u64_integer() {
+    test_stringify(u64::MAX, "¢LygHa16AHYF");
+    test_stringify(Value::Number(UInt(u64::MAX)), "¢LygHa16AHYF");
+    test_stringify(i64::MIN, "¢-AzL8n0Y58m8");
+    test_stringify(
+        vec![u64::MAX, u64::MAX - 1, u64::MAX],
+        "|¢LygHa16AHYF¢LygHa16AHYEº0÷",
+    );
+}
+
+#[test]
+fn test_unsafe_integers() {
+    let with_policy = |unsafe_integers| SerializerOptions {
+        unsafe_integers,
+        ..SerializerOptions::default()
+    };
+
+    test_stringify_with_options(
+        vec![9007199254740991i64, -9007199254740991],
+        with_policy(UnsafeIntegers::Error),
+        "|¢fFgnDxSe7¢-fFgnDxSe7÷",
+    );
+    test_stringify_with_options(
+        vec![9007199254740992u64, 9007199254740992],
+        with_policy(UnsafeIntegers::Allow),
+        "|¢fFgnDxSe8º0÷",
+    );
+    test_stringify_with_options(
+        vec![9007199254740992u64, u64::MAX],
+        with_policy(UnsafeIntegers::String),
+        "|¨9007199254740992¨¨18446744073709551615¨÷",
+    );
+    test_stringify_with_options(
+        vec![-9007199254740993i64, i64::MAX],
+        with_policy(UnsafeIntegers::Float),
+        "|£-fFgnDxSe8.0£9,223372036854776e+18÷",
+    );
+
+    let res = to_string_with_options(&u64::MAX, with_policy(UnsafeIntegers::Error));
+    assert!(matches!(res, Err(Error::UnsafeInteger)));
+}
+
 #[test]
 fn test_unreferenced_integer() {
     test_stringify(
@@ -96,6 +147,17 @@ fn test_float_small() {
     test_stringify(Value::Number(Float(-15.555)), "£-F.-8x");
 }
 
+#[test]
+fn test_float_negative() {
+    test_stringify(Value::Number(Float(-1.5)), "£-1.-84");
+    test_stringify(Value::Number(Float(-0.5)), "£0.-84");
+    test_stringify(Value::Number(Float(-123.456)), "£-1z.-7M");
+    // halves round towards positive infinity
+    test_stringify(Value::Number(Float(-0.0025)), "£0.-2");
+    test_stringify(Value::Number(Float(0.0025)), "£0.3");
+    test_stringify(Value::Number(Float(-0.9996)), "£0.-G8");
+}
+
 #[test]
 fn test_float_full_precision() {
     test_stringify_full_precision(Value::Number(Float(0.)), "£0,0");
@@ -106,6 +168,133 @@ fn test_float_full_precision() {
     test_stringify_full_precision(Value::Number(Float(-0.552345411)), "£-0,552345411");
 }
 
+#[test]
+fn test_float_auto_precision() {
+    let options = SerializerOptions {
+        float_precision: FloatPrecision::Auto,
+        ..SerializerOptions::default()
+    };
+
+    test_stringify_with_options(Value::Number(Float(12.5)), options.clone(), "£C.84");
+    test_stringify_with_options(Value::Number(Float(-0.1)), options.clone(), "£0.-1c");
+    test_stringify_with_options(Value::Number(Float(1.23456)), options.clone(), "£1,23456");
+    test_stringify_with_options(Value::Number(Float(1e-10)), options.clone(), "£1,0e-10");
+    test_stringify_with_options(
+        Value::Array(vec![
+            Value::Number(Float(0.001)),
+            Value::Number(Float(0.0001)),
+        ]),
+        options,
+        "|£0.1£0,0001÷",
+    );
+}
+
+#[test]
+fn test_float_integral() {
+    let options = SerializerOptions {
+        integral_floats_as_integers: true,
+        ..SerializerOptions::default()
+    };
+
+    test_stringify(Value::Number(Float(1.)), "£1.0");
+    test_stringify_with_options(Value::Number(Float(1.)), options.clone(), "Ê");
+    test_stringify_with_options(
+        Value::Array(vec![
+            Value::Number(Float(-0.)),
+            Value::Number(Float(123.)),
+            Value::Number(Float(1.5)),
+            Value::Number(Float(1e20)),
+        ]),
+        options.clone(),
+        "|É¢1z£1.84£1,0e+20÷",
+    );
+
+    for unsafe_integers in [
+        UnsafeIntegers::Allow,
+        UnsafeIntegers::Error,
+        UnsafeIntegers::String,
+        UnsafeIntegers::Float,
+    ] {
+        let options = SerializerOptions {
+            unsafe_integers,
+            ..options.clone()
+        };
+        let floats = vec![9007199254740991f64, -9007199254740992., 1e17];
+
+        test_stringify_with_options(
+            floats.clone(),
+            options.clone(),
+            "|¢fFgnDxSe7£-fFgnDxSe8.0£7O044qYiZc.0÷",
+        );
+
+        let res = to_string_with_options(&floats, options).unwrap();
+        assert_eq!(
+            serde_zipson::de::from_str::<Vec<f64>>(&res).unwrap(),
+            floats
+        );
+    }
+}
+
+#[test]
+fn test_f32() {
+    test_stringify(0.1f32, "£0.1c");
+    test_stringify_full_precision(0.1f32, "£0,1");
+    test_stringify_full_precision(-16777217f32, "£-18OWG,0");
+    test_stringify_full_precision(1e-10f32, "£1,0e-10");
+    test_stringify_full_precision(f32::MAX, "£3,4028235e+38");
+}
+
+#[test]
+fn test_f32_round_trip() {
+    let mut bits = 1u32;
+    for _ in 0..10000 {
+        bits = bits.wrapping_mul(1664525).wrapping_add(1013904223);
+        let v = f32::from_bits(bits);
+        if !v.is_finite() {
+            continue;
+        }
+
+        let res = to_string(&v, true, false).unwrap();
+        assert_eq!(
+            serde_zipson::de::from_str::<f32>(&res).unwrap(),
+            v,
+            "{}",
+            res
+        );
+    }
+}
+
+#[test]
+fn test_float_exponent() {
+    test_stringify(Value::Number(Float(1e300)), "£1,0e+300");
+    test_stringify(Value::Number(Float(f64::MIN)), "£-1,7976931348623157e+308");
+    test_stringify(Value::Number(Float(1e-10)), "£0.0");
+    test_stringify_full_precision(Value::Number(Float(1e-10)), "£1,0e-10");
+    test_stringify_full_precision(Value::Number(Float(-1.5e-10)), "£-1,5e-10");
+    test_stringify_full_precision(Value::Number(Float(5e-324)), "£5,0e-324");
+}
+
+#[test]
+fn test_float_non_finite() {
+    test_stringify(
+        Value::Array(vec![
+            Value::Number(Float(f64::NAN)),
+            Value::Number(Float(f64::INFINITY)),
+            Value::Number(Float(f64::NEG_INFINITY)),
+        ]),
+        "|§þþ÷",
+    );
+
+    let res = to_string_with_options(
+        &f64::NAN,
+        SerializerOptions {
+            non_finite_floats: NonFiniteFloats::Error,
+            ..SerializerOptions::default()
+        },
+    );
+    assert!(matches!(res, Err(Error::FloatMustBeFinite)));
+}
+
 #[test]
 fn test_unreferenced_float() {
     let mut values = vec![];
@@ -291,3 +480,91 @@ fn test_ref_lp_date() {
         "|±1739m±1739sü0ü1ü0ü1÷",
     );
 }
+
+#[test]
+fn test_pre_1970_date() {
+    test_stringify_detect_dates(
+        Value::Array(vec![
+            Value::String("1969-12-31T23:59:59.999Z".into()),
+            Value::String("1901-03-04T05:06:07.089Z".into()),
+            Value::String("1960-01-01T00:00:00.000Z".into()),
+        ]),
+        "|ø-1ø-cEv2OGF±-DF4K÷",
+    );
+}
+
+#[test]
+fn test_offset_date() {
+    test_stringify_detect_dates(
+        Value::String("2022-02-24T06:31:00.123+02:00".into()),
+        "¨2022-02-24T06:31:00.123+02:00¨",
+    );
+    test_stringify_with_options(
+        Value::Array(vec![
+            Value::String("2022-02-24T06:31:00.123+02:00".into()),
+            Value::String("2022-02-23T23:31:00.123-05:00".into()),
+        ]),
+        SerializerOptions {
+            detect_utc_timestamps: true,
+            normalize_timestamp_offsets: true,
+            ..SerializerOptions::default()
+        },
+        "|øSyKTET5×0÷",
+    );
+}
+
+#[test]
+fn test_non_canonical_date() {
+    test_stringify_detect_dates(
+        Value::Array(vec![
+            Value::String("2020-01-01T00:00:00Z".into()),
+            Value::String("2020-01-01T00:00:00.1Z".into()),
+            Value::String("2020-01-01T00:00:00.000001Z".into()),
+            Value::String("2020-01-01T00:00:00.000Z".into()),
+        ]),
+        "|¨2020-01-01T00:00:00Z¨¨2020-01-01T00:00:00.1Z¨¨2020-01-01T00:00:00.000001Z¨±14Cfo÷",
+    );
+    test_stringify_with_options(
+        Value::String("2022-02-24T06:31:00.123456+02:00".into()),
+        SerializerOptions {
+            detect_utc_timestamps: true,
+            normalize_timestamp_offsets: true,
+            ..SerializerOptions::default()
+        },
+        "¨2022-02-24T06:31:00.123456+02:00¨",
+    );
+}
+
+#[test]
+fn test_invalid_date() {
+    test_stringify_detect_dates(
+        Value::Array(vec![
+            Value::String("2023-02-29T00:00:00.000Z".into()),
+            Value::String("2022-13-01T00:00:00.000Z".into()),
+            Value::String("2022-01-01T24:00:00.000Z".into()),
+            Value::String("2022-01-01T00:00:00.000z".into()),
+            Value::String("2022-01-01 00:00:00.000Z".into()),
+            Value::String("2024-02-29T00:00:00.000Z".into()),
+        ]),
+        "|¨2023-02-29T00:00:00.000Z¨¨2022-13-01T00:00:00.000Z¨¨2022-01-01T24:00:00.000Z¨¨2022-01-01T00:00:00.000z¨¨2022-01-01 00:00:00.000Z¨±19iJk÷",
+    );
+}
+
+#[test]
+fn test_date_round_trip() {
+    let mut millis = -62_135_596_800_000_i64;
+    while millis < 253_402_300_799_999 {
+        let date = DateTime::from_timestamp_millis(millis)
+            .unwrap()
+            .to_rfc3339_opts(SecondsFormat::Millis, true);
+        let res = to_string(&date, false, true).unwrap();
+        assert!(!res.starts_with('¨'), "{} was not detected", date);
+        assert_eq!(
+            serde_zipson::de::from_str::<i64>(&res).unwrap(),
+            millis,
+            "{}",
+            date
+        );
+        millis += 987_654_321_123;
+    }
+}
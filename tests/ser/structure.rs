@@ -1,8 +1,17 @@
-use crate::ser::test_stringify;
+use crate::ser::{test_stringify, test_stringify_with_options};
 
+#[cfg(all(feature = "chrono", feature = "std"))]
+use chrono::{DateTime, Utc};
+use chrono::{NaiveDate, NaiveTime};
 use indexmap::{indexmap, IndexMap};
 use serde::Serialize;
+use serde_zipson::ser::{
+    FloatPrecision, Rfc3339TimestampDetector, SerializerOptions, TimestampDetector,
+};
 use serde_zipson::value::{Number, Value};
+use std::sync::Arc;
+#[cfg(all(feature = "chrono", feature = "std"))]
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 #[test]
 fn test_empty() {
@@ -49,6 +58,7 @@ fn test_mixed() {
     );
 }
 
+#[cfg(feature = "preserve_order")]
 #[test]
 fn test_nested() {
     #[derive(Serialize)]
@@ -123,6 +133,7 @@ fn nest_newtype() {
     test_stringify(IntNewType(123), "¢1z");
 }
 
+#[cfg(feature = "preserve_order")]
 #[test]
 fn nest_newtype_nested() {
     #[derive(Serialize)]
@@ -166,6 +177,7 @@ fn test_unit() {
     test_stringify(UnitStruct(()), "§");
 }
 
+#[cfg(feature = "preserve_order")]
 #[test]
 fn test_tuple() {
     #[derive(Serialize)]
@@ -208,3 +220,418 @@ fn test_tuple() {
         "|¨string¨¢EMnFO§{´x´Ê´y´º0¨float¨£0.52´z´¨asdfioj{{}}¨´i´´´¨longkey¨»¨nope¨§¨yep¨{´5´|§÷ß0¨\"\"asoidj{}sidofj¨}}÷",
     );
 }
+
+#[test]
+fn test_flatten() {
+    #[derive(Serialize)]
+    struct Envelope {
+        id: i64,
+        #[serde(flatten)]
+        meta: Meta,
+        #[serde(flatten)]
+        extra: Option<Extra>,
+    }
+
+    #[derive(Serialize)]
+    struct Meta {
+        version: i64,
+        #[serde(flatten)]
+        inner: Inner,
+    }
+
+    #[derive(Serialize)]
+    struct Inner {
+        source: String,
+        ratio: f64,
+    }
+
+    #[derive(Serialize)]
+    struct Extra {
+        note: String,
+    }
+
+    test_stringify(
+        Envelope {
+            id: 1,
+            meta: Meta {
+                version: 2,
+                inner: Inner {
+                    source: "src".into(),
+                    ratio: 1.5,
+                },
+            },
+            extra: None,
+        },
+        "{¨id¨Ê¨version¨Ë¨source¨¨src¨¨ratio¨£1.84}",
+    );
+    test_stringify(
+        Envelope {
+            id: 1,
+            meta: Meta {
+                version: 2,
+                inner: Inner {
+                    source: "src".into(),
+                    ratio: 1.5,
+                },
+            },
+            extra: Some(Extra { note: "hi".into() }),
+        },
+        "{¨id¨Ê¨version¨Ë¨source¨¨src¨¨ratio¨£1.84¨note¨¨hi¨}",
+    );
+}
+
+#[test]
+fn test_flatten_map() {
+    #[derive(Serialize)]
+    struct Object {
+        id: i64,
+        #[serde(flatten)]
+        rest: IndexMap<String, Value>,
+    }
+
+    test_stringify(
+        Object {
+            id: 1,
+            rest: indexmap! {
+                "a".into() => Value::Number(Number::Int(1)),
+                "b".into() => Value::Number(Number::Int(300)),
+            },
+        },
+        "{¨id¨Ê´a´Ê´b´¢4q}",
+    );
+}
+
+#[test]
+fn test_flatten_non_string_key() {
+    #[derive(Serialize)]
+    struct Object {
+        #[serde(flatten)]
+        rest: IndexMap<(), i64>,
+    }
+
+    let res = serde_zipson::ser::to_string(
+        &Object {
+            rest: indexmap! { () => 1 },
+        },
+        false,
+        false,
+    );
+    assert!(matches!(
+        res,
+        Err(serde_zipson::error::Error::KeyMustBeAString)
+    ));
+}
+
+#[test]
+fn test_compact() {
+    #[derive(Serialize)]
+    struct Point {
+        x: i64,
+        y: i64,
+        label: String,
+        nested: Nested,
+    }
+
+    #[derive(Serialize)]
+    struct Nested {
+        a: f64,
+        b: Option<i64>,
+    }
+
+    #[derive(Serialize)]
+    struct Same {
+        a: i64,
+        b: i64,
+        c: i64,
+        d: i64,
+        e: i64,
+        f: i64,
+    }
+
+    #[derive(Serialize)]
+    enum StructLike {
+        Value { id: i64, name: String },
+    }
+
+    #[derive(Serialize)]
+    #[serde(tag = "t", content = "c")]
+    enum Adjacent {
+        Value { id: i64, name: String },
+    }
+
+    let options = SerializerOptions {
+        compact_structs: true,
+        ..SerializerOptions::default()
+    };
+
+    test_stringify_with_options(
+        Point {
+            x: 1,
+            y: 2,
+            label: "point".into(),
+            nested: Nested { a: 1.5, b: None },
+        },
+        options.clone(),
+        "|ÊË¨point¨|£1.84§÷÷",
+    );
+    test_stringify_with_options(
+        vec![
+            Point {
+                x: 1,
+                y: 2,
+                label: "point".into(),
+                nested: Nested { a: 1.5, b: None },
+            },
+            Point {
+                x: 1,
+                y: 2,
+                label: "point".into(),
+                nested: Nested { a: 1.5, b: Some(3) },
+            },
+        ],
+        options.clone(),
+        "||ÊË¨point¨|£1.84§÷÷|ÊËß0|Ý0Ì÷÷÷",
+    );
+    test_stringify_with_options(
+        Same {
+            a: 1,
+            b: 1,
+            c: 1,
+            d: 1,
+            e: 1,
+            f: 1,
+        },
+        options.clone(),
+        "|Êþþþ^2÷",
+    );
+    test_stringify_with_options(
+        StructLike::Value {
+            id: 1,
+            name: "n".into(),
+        },
+        options.clone(),
+        "{¨Value¨|Ê´n´÷}",
+    );
+    test_stringify_with_options(
+        Adjacent::Value {
+            id: 1,
+            name: "n".into(),
+        },
+        options,
+        "|¨Value¨{¨id¨Ê¨name¨´n´}÷",
+    );
+}
+
+#[test]
+fn test_compact_skipped_field() {
+    #[derive(Serialize)]
+    struct Object {
+        x: i64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        y: Option<i64>,
+    }
+
+    let res = serde_zipson::ser::to_string_with_options(
+        &Object { x: 1, y: None },
+        SerializerOptions {
+            compact_structs: true,
+            ..SerializerOptions::default()
+        },
+    );
+    assert!(res.is_err());
+}
+
+#[cfg(all(feature = "chrono", feature = "std"))]
+#[test]
+fn test_dates() {
+    #[derive(Serialize)]
+    struct Event {
+        #[serde(with = "serde_zipson::date")]
+        at: DateTime<Utc>,
+        #[serde(with = "serde_zipson::date")]
+        again: DateTime<Utc>,
+        #[serde(with = "serde_zipson::date")]
+        system: SystemTime,
+        raw: String,
+    }
+
+    test_stringify(
+        Event {
+            at: "2022-02-24T04:31:00.123Z".parse().unwrap(),
+            again: "2022-02-24T04:31:00.123+00:00".parse().unwrap(),
+            system: UNIX_EPOCH + Duration::from_secs(1645677000),
+            raw: "2022-02-24T04:31:00.123Z".into(),
+        },
+        "{¨at¨øSyKTET5¨again¨×0¨system¨±1739m¨raw¨¨2022-02-24T04:31:00.123Z¨}",
+    );
+
+    #[derive(Serialize)]
+    struct Before {
+        #[serde(with = "serde_zipson::date")]
+        at: DateTime<Utc>,
+        #[serde(with = "serde_zipson::date")]
+        system: SystemTime,
+    }
+
+    test_stringify(
+        Before {
+            at: DateTime::from_timestamp_nanos(-1_500_000),
+            system: UNIX_EPOCH - Duration::from_micros(1_500),
+        },
+        "{¨at¨ø-2¨system¨×0}",
+    );
+}
+
+#[test]
+fn test_timestamp_detector() {
+    struct FieldDetector;
+
+    impl TimestampDetector for FieldDetector {
+        fn detect(&self, key: Option<&str>, v: &str) -> Option<i64> {
+            match key {
+                Some("code") => None,
+                Some("day") => NaiveDate::parse_from_str(v, "%Y/%m/%d")
+                    .ok()
+                    .map(|day| day.and_time(NaiveTime::MIN).and_utc().timestamp_millis()),
+                _ => Rfc3339TimestampDetector::default().detect(key, v),
+            }
+        }
+    }
+
+    #[derive(Serialize)]
+    struct Object {
+        code: String,
+        day: String,
+        at: String,
+    }
+
+    test_stringify_with_options(
+        Object {
+            code: "2022-02-24T04:30:00.000Z".into(),
+            day: "2022/02/24".into(),
+            at: "2022-02-24T04:30:00.000Z".into(),
+        },
+        SerializerOptions {
+            detect_utc_timestamps: true,
+            timestamp_detector: Some(Arc::new(FieldDetector)),
+            ..SerializerOptions::default()
+        },
+        "{¨code¨¨2022-02-24T04:30:00.000Z¨¨day¨±1737A¨at¨±1739m}",
+    );
+    test_stringify_with_options(
+        Object {
+            code: "2022-02-24T04:30:00.000Z".into(),
+            day: "2022/02/24".into(),
+            at: "2022-02-24T04:30:00.000Z".into(),
+        },
+        SerializerOptions {
+            detect_utc_timestamps: true,
+            timestamp_detector: Some(Arc::new(FieldDetector)),
+            compact_structs: true,
+            ..SerializerOptions::default()
+        },
+        "|¨2022-02-24T04:30:00.000Z¨±1737A±1739m÷",
+    );
+}
+
+#[test]
+fn test_float_precision_fields() {
+    #[derive(Serialize)]
+    struct Reading {
+        #[serde(with = "serde_zipson::float::reduced")]
+        temperature: f64,
+        #[serde(with = "serde_zipson::float::full")]
+        price: Option<f64>,
+        #[serde(with = "serde_zipson::float::full")]
+        history: Vec<f64>,
+        other: f64,
+    }
+
+    let reading = Reading {
+        temperature: 21.123456,
+        price: Some(9.9999),
+        history: vec![1.23456],
+        other: 0.123456,
+    };
+
+    test_stringify(
+        &reading,
+        "{¨temperature¨£L.1z¨price¨£9,9999¨history¨|£1,23456÷¨other¨£0.1z}",
+    );
+    test_stringify_with_options(
+        &reading,
+        SerializerOptions {
+            float_precision: FloatPrecision::Full,
+            ..SerializerOptions::default()
+        },
+        "{¨temperature¨£L.1z¨price¨£9,9999¨history¨|£1,23456÷¨other¨£0,123456}",
+    );
+}
+
+#[cfg(feature = "decimal")]
+#[test]
+fn test_decimal_fields() {
+    use rust_decimal::Decimal;
+
+    #[derive(Serialize)]
+    struct Payment {
+        #[serde(with = "serde_zipson::decimal")]
+        amount: Decimal,
+        #[serde(with = "serde_zipson::decimal")]
+        fee: Decimal,
+        #[serde(with = "serde_zipson::decimal")]
+        total: Decimal,
+        #[serde(with = "serde_zipson::decimal")]
+        refund: Decimal,
+        #[serde(with = "serde_zipson::decimal")]
+        units: Decimal,
+    }
+
+    test_stringify(
+        Payment {
+            amount: "12.50".parse().unwrap(),
+            fee: "-0.05".parse().unwrap(),
+            total: "79228162514264337593543950335".parse().unwrap(),
+            refund: "12.50".parse().unwrap(),
+            units: "12".parse().unwrap(),
+        },
+        "{¨amount¨£C,50¨fee¨£-0,05¨total¨¢1f2SI9UJPXvb7vdJ1¨refund¨Ý0¨units¨¤C}",
+    );
+    test_stringify(
+        Payment {
+            amount: "12.0".parse().unwrap(),
+            fee: "-9".parse().unwrap(),
+            total: "12".parse().unwrap(),
+            refund: "0".parse().unwrap(),
+            units: "9007199254740993".parse().unwrap(),
+        },
+        "{¨amount¨£C,0¨fee¨À¨total¨¤C¨refund¨É¨units¨¢fFgnDxSe9}",
+    );
+}
+
+#[cfg(feature = "time")]
+#[test]
+fn test_time_dates() {
+    use time::{OffsetDateTime, UtcOffset};
+
+    #[derive(Serialize)]
+    struct Event {
+        #[serde(with = "serde_zipson::date")]
+        at: OffsetDateTime,
+        #[serde(with = "serde_zipson::date")]
+        local: OffsetDateTime,
+        #[serde(with = "serde_zipson::date")]
+        before: OffsetDateTime,
+    }
+
+    let at = OffsetDateTime::from_unix_timestamp_nanos(1_645_677_060_123_000_000).unwrap();
+
+    test_stringify(
+        Event {
+            at,
+            local: at.to_offset(UtcOffset::from_hms(2, 0, 0).unwrap()),
+            before: OffsetDateTime::from_unix_timestamp_nanos(-1_500_000).unwrap(),
+        },
+        "{¨at¨øSyKTET5¨local¨×0¨before¨ø-2}",
+    );
+}